@@ -0,0 +1,106 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{commands::error::CommandError, model::attachment::Attachment, UserState};
+use tauri::{api::path::app_dir, command, AppHandle, State, Wry};
+use zeroize::Zeroizing;
+
+/// the content is transferred base64 encoded
+#[command]
+pub fn add_attachment(
+  uuid: String,
+  name: String,
+  data: String,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<Attachment, CommandError> {
+  let data = Zeroizing::new(
+    base64::decode(data).map_err(|error| CommandError::InvalidInput(error.to_string()))?,
+  );
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => {
+      let directory = app_dir(&*handle.config()).unwrap();
+      // undone if it can not be saved
+      Ok(user.apply(&directory, |user| {
+        user.add_attachment(&directory, uuid.as_str(), name, data.as_slice())
+      })?)
+    }
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn list_attachments(
+  uuid: String,
+  state: State<'_, UserState>,
+) -> Result<Vec<Attachment>, CommandError> {
+  // get the user
+  match &*state.0.lock().unwrap() {
+    Some(user) => Ok(user.attachments(uuid.as_str())?),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+/// get the base64 encoded content
+#[command]
+pub fn download_attachment(
+  uuid: String,
+  id: String,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<String, CommandError> {
+  // get the user
+  match &*state.0.lock().unwrap() {
+    Some(user) => {
+      let data = user.read_attachment(
+        &app_dir(&*handle.config()).unwrap(),
+        uuid.as_str(),
+        id.as_str(),
+      )?;
+      Ok(base64::encode(data.as_slice()))
+    }
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn delete_attachment(
+  uuid: String,
+  id: String,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<(), CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => {
+      // undone if it can not be saved
+      Ok(user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        user.delete_attachment(uuid.as_str(), id.as_str())
+      })?)
+    }
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
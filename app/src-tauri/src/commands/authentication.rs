@@ -23,48 +23,157 @@
  * SOFTWARE.
  */
 
-use crate::{model::user::UserData, User, UserState};
+use crate::{
+  commands::{error::CommandError, session::lock_vault},
+  model::user::{ConfigError, UserData},
+  SessionState, User, UserState,
+};
 use tauri::{api::path::app_dir, command, AppHandle, State, Wry};
 
 #[command]
 pub fn login(
   data: UserData,
   state: State<'_, UserState>,
+  session: State<'_, SessionState>,
   handle: AppHandle<Wry>,
-) -> Result<(), ()> {
-  // create the user from the data
-  match User::new_from_login(&app_dir(&*handle.config()).unwrap(), data) {
-    Ok(user) => {
-      // update data in state
-      *state.0.lock().unwrap() = Some(user);
-      Ok(())
+) -> Result<(), CommandError> {
+  let directory = app_dir(&*handle.config()).unwrap();
+  let mut state = state.0.lock().unwrap();
+
+  // create the user from the data, a failed login keeps the current session
+  let user = match User::new_from_login(&directory, data.clone()) {
+    Ok(user) => user,
+    // the vault is held by the current session, released once the password has been verified
+    Err(ConfigError::LockedError)
+      if state
+        .as_ref()
+        .map_or(false, |user| user.verify_login(&data)) =>
+    {
+      if let Some(mut user) = state.take() {
+        user.wipe();
+      }
+      User::new_from_login(&directory, data)?
     }
-    Err(_) => Err(()),
+    Err(error) => return Err(error.into()),
+  };
+  // start tracking the activity
+  session
+    .0
+    .lock()
+    .unwrap()
+    .start(user.username(), user.auto_lock());
+  // update data in state
+  if let Some(mut previous) = state.replace(user) {
+    previous.wipe();
   }
+  Ok(())
 }
 
 #[command]
 pub fn signup(
   data: UserData,
   state: State<'_, UserState>,
+  session: State<'_, SessionState>,
   handle: AppHandle<Wry>,
-) -> Result<(), ()> {
+) -> Result<(), CommandError> {
   // prevent already existing users creating new ones
   if let Some(_) = *state.0.lock().unwrap() {
-    return Err(());
+    return Err(CommandError::LoggedIn);
   }
   // try the signup
-  match User::new_from_signup(&app_dir(&*handle.config()).unwrap(), data) {
-    Ok(user) => {
-      // update the user
-      *state.0.lock().unwrap() = Some(user);
-      Ok(())
+  let user = User::new_from_signup(&app_dir(&*handle.config()).unwrap(), data)?;
+  session
+    .0
+    .lock()
+    .unwrap()
+    .start(user.username(), user.auto_lock());
+  // update the user
+  *state.0.lock().unwrap() = Some(user);
+  Ok(())
+}
+
+#[command]
+pub fn logout(state: State<'_, UserState>, session: State<'_, SessionState>) {
+  // wipe the decrypted data before the memory gets released
+  if let Some(mut user) = state.0.lock().unwrap().take() {
+    user.wipe();
+  }
+  session.0.lock().unwrap().stop();
+}
+
+#[command]
+pub fn lock(handle: AppHandle<Wry>) {
+  lock_vault(&handle);
+}
+
+/// unlock the vault of the locked session again, only the password is needed
+#[command]
+pub fn unlock(
+  password: String,
+  state: State<'_, UserState>,
+  session: State<'_, SessionState>,
+  handle: AppHandle<Wry>,
+) -> Result<(), CommandError> {
+  let username = session
+    .0
+    .lock()
+    .unwrap()
+    .username()
+    .ok_or(CommandError::NotLoggedIn)?;
+  login(UserData::new(username, password), state, session, handle)
+}
+
+#[command]
+pub fn change_master_password(
+  current: String,
+  password: String,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<(), CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => Ok(user.change_password(
+      &app_dir(&*handle.config()).unwrap(),
+      current.as_str(),
+      password.as_str(),
+    )?),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn rotate_vault_key(
+  password: String,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<(), CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => {
+      Ok(user.rotate_vault_key(&app_dir(&*handle.config()).unwrap(), password.as_str())?)
     }
-    Err(_) => Err(()),
+    None => Err(CommandError::NotLoggedIn),
   }
 }
 
 #[command]
-pub fn logout(state: State<'_, UserState>) {
-  *state.0.lock().unwrap() = None;
+pub fn get_private_metadata(state: State<'_, UserState>) -> Result<bool, CommandError> {
+  // get the user
+  match &*state.0.lock().unwrap() {
+    Some(user) => Ok(user.private_metadata()),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn update_private_metadata(
+  enabled: bool,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<(), CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => Ok(user.update_private_metadata(&app_dir(&*handle.config()).unwrap(), enabled)?),
+    None => Err(CommandError::NotLoggedIn),
+  }
 }
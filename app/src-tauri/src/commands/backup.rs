@@ -0,0 +1,83 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{commands::error::CommandError, model::backup::BackupStatus, UserState};
+use tauri::{api::path::app_dir, command, AppHandle, State, Wry};
+
+#[command]
+pub fn enable_backup(
+  url: String,
+  uuid: Option<String>,
+  password: Option<String>,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<(), CommandError> {
+  // link an existing account only with complete credentials
+  let credentials = match (uuid, password) {
+    (Some(uuid), Some(password)) => Some((uuid, password)),
+    (None, None) => None,
+    _ => {
+      return Err(CommandError::InvalidInput(String::from(
+        "Both the uuid and the password of the backup are required",
+      )))
+    }
+  };
+
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => Ok(user.enable_backup(&app_dir(&*handle.config()).unwrap(), url, credentials)?),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn push_backup(state: State<'_, UserState>) -> Result<(), CommandError> {
+  // get the user
+  match &*state.0.lock().unwrap() {
+    Some(user) => Ok(user.push_backup()?),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn pull_backup(
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<(), CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => Ok(user.pull_backup(&app_dir(&*handle.config()).unwrap())?),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn backup_status(state: State<'_, UserState>) -> Result<BackupStatus, CommandError> {
+  // get the user
+  match &*state.0.lock().unwrap() {
+    Some(user) => Ok(user.backup_status()),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
@@ -0,0 +1,206 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::model::{
+  encryption::EncryptionError, import::ImportError, index::IndexError, sync::SyncError,
+  user::ConfigError,
+};
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+use std::io::ErrorKind;
+use thiserror::Error;
+
+/// error returned by every command
+///
+/// serialized as `{ "code": "...", "message": "..." }`, the codes are stable for the frontend
+#[derive(Error, Debug)]
+pub enum CommandError {
+  #[error("Not logged in")]
+  NotLoggedIn,
+
+  #[error("Already logged in")]
+  LoggedIn,
+
+  #[error("Invalid credentials")]
+  InvalidCredentials,
+
+  #[error("User already exists")]
+  UserExists,
+
+  #[error("Not found")]
+  NotFound,
+
+  #[error("{0}")]
+  Io(String),
+
+  #[error("{0}")]
+  Corrupt(String),
+
+  #[error("{0}")]
+  Crypto(String),
+
+  #[error("Vault has been modified by another session")]
+  Conflict,
+
+  #[error("Vault is opened by another session")]
+  Locked,
+
+  #[error("{0}")]
+  Unsupported(String),
+
+  #[error("Backup is not enabled")]
+  BackupDisabled,
+
+  #[error("{0}")]
+  Sync(String),
+
+  #[error("{0}")]
+  InvalidInput(String),
+}
+
+impl CommandError {
+  /// the stable identifier of the error
+  pub fn code(&self) -> &'static str {
+    match self {
+      CommandError::NotLoggedIn => "NOT_LOGGED_IN",
+      CommandError::LoggedIn => "LOGGED_IN",
+      CommandError::InvalidCredentials => "INVALID_CREDENTIALS",
+      CommandError::UserExists => "USER_EXISTS",
+      CommandError::NotFound => "NOT_FOUND",
+      CommandError::Io(_) => "IO",
+      CommandError::Corrupt(_) => "CORRUPT",
+      CommandError::Crypto(_) => "CRYPTO",
+      CommandError::Conflict => "CONFLICT",
+      CommandError::Locked => "LOCKED",
+      CommandError::Unsupported(_) => "UNSUPPORTED",
+      CommandError::BackupDisabled => "BACKUP_DISABLED",
+      CommandError::Sync(_) => "SYNC",
+      CommandError::InvalidInput(_) => "INVALID_INPUT",
+    }
+  }
+}
+
+impl Serialize for CommandError {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut state = serializer.serialize_struct("CommandError", 2)?;
+    state.serialize_field("code", self.code())?;
+    state.serialize_field("message", &self.to_string())?;
+    state.end()
+  }
+}
+
+impl From<ConfigError> for CommandError {
+  fn from(error: ConfigError) -> Self {
+    match error {
+      ConfigError::IOError(error) if error.kind() == ErrorKind::NotFound => CommandError::NotFound,
+      ConfigError::IOError(error) => CommandError::Io(error.to_string()),
+      ConfigError::ParseError(error) => CommandError::Corrupt(error.to_string()),
+      ConfigError::FormatError => CommandError::Corrupt(error.to_string()),
+      ConfigError::CorruptError => CommandError::Corrupt(error.to_string()),
+      ConfigError::EncryptionError(error) => error.into(),
+      ConfigError::SyncError(error) => error.into(),
+      ConfigError::FieldError(error) => CommandError::InvalidInput(error.to_string()),
+      ConfigError::OtpError(error) => CommandError::InvalidInput(error.to_string()),
+      ConfigError::HashError(error) => CommandError::Crypto(error.to_string()),
+      ConfigError::IndexError(error) => error.into(),
+      ConfigError::VersionError(_) => CommandError::Unsupported(error.to_string()),
+      ConfigError::LockedError => CommandError::Locked,
+      ConfigError::ConflictError => CommandError::Conflict,
+      ConfigError::UserExistsError => CommandError::UserExists,
+      ConfigError::BackupError => CommandError::BackupDisabled,
+      ConfigError::EmptyBackupError => CommandError::Sync(error.to_string()),
+      ConfigError::NotFoundError => CommandError::NotFound,
+      ConfigError::FolderCycleError => CommandError::InvalidInput(error.to_string()),
+      ConfigError::AttachmentSizeError => CommandError::InvalidInput(error.to_string()),
+      // wrong password
+      ConfigError::Unknown => CommandError::InvalidCredentials,
+    }
+  }
+}
+
+impl From<EncryptionError> for CommandError {
+  fn from(error: EncryptionError) -> Self {
+    CommandError::Crypto(error.to_string())
+  }
+}
+
+impl From<IndexError> for CommandError {
+  fn from(error: IndexError) -> Self {
+    match error {
+      IndexError::IOError(error) => CommandError::Io(error.to_string()),
+      IndexError::ParseError(error) => CommandError::Corrupt(error.to_string()),
+      IndexError::LockedError => CommandError::Locked,
+      _ => CommandError::Crypto(error.to_string()),
+    }
+  }
+}
+
+impl From<ImportError> for CommandError {
+  fn from(error: ImportError) -> Self {
+    match error {
+      ImportError::IOError(error) if error.kind() == ErrorKind::NotFound => CommandError::NotFound,
+      ImportError::IOError(error) => CommandError::Io(error.to_string()),
+      ImportError::DecryptError => CommandError::InvalidCredentials,
+      ImportError::Unsupported(message) => CommandError::Unsupported(message),
+      _ => CommandError::InvalidInput(error.to_string()),
+    }
+  }
+}
+
+impl From<SyncError> for CommandError {
+  fn from(error: SyncError) -> Self {
+    CommandError::Sync(error.to_string())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_serialize() {
+    let error = CommandError::from(ConfigError::Unknown);
+    assert_eq!(
+      serde_json::to_value(&error).unwrap(),
+      serde_json::json!({ "code": "INVALID_CREDENTIALS", "message": "Invalid credentials" })
+    );
+  }
+
+  #[test]
+  fn test_config_error() {
+    let missing = std::io::Error::new(ErrorKind::NotFound, "missing");
+    assert_eq!(
+      CommandError::from(ConfigError::from(missing)).code(),
+      "NOT_FOUND"
+    );
+    assert_eq!(
+      CommandError::from(ConfigError::ConflictError).code(),
+      "CONFLICT"
+    );
+    assert_eq!(
+      CommandError::from(ConfigError::from(EncryptionError::NonceError)).code(),
+      "CRYPTO"
+    );
+  }
+}
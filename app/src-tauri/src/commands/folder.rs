@@ -0,0 +1,130 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{commands::error::CommandError, model::folder::Folder, UserState};
+use tauri::{api::path::app_dir, command, AppHandle, State, Wry};
+
+#[command]
+pub fn get_folders(state: State<'_, UserState>) -> Result<Vec<Folder>, CommandError> {
+  // get the user
+  match &*state.0.lock().unwrap() {
+    Some(user) => Ok(user.folders()),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn new_folder(
+  name: String,
+  parent: Option<String>,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<Folder, CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => {
+      // undone if it can not be saved
+      Ok(user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        user.new_folder(name, parent)
+      })?)
+    }
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn rename_folder(
+  uuid: String,
+  name: String,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<(), CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => {
+      // undone if it can not be saved
+      Ok(user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        user.rename_folder(uuid.as_str(), name)
+      })?)
+    }
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn move_folder(
+  uuid: String,
+  parent: Option<String>,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<(), CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => {
+      // undone if it can not be saved
+      Ok(user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        user.move_folder(uuid.as_str(), parent)
+      })?)
+    }
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn delete_folder(
+  uuid: String,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<(), CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => {
+      // undone if it can not be saved
+      Ok(user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        user.delete_folder(uuid.as_str())
+      })?)
+    }
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn move_password(
+  uuid: String,
+  folder: Option<String>,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<(), CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => {
+      // undone if it can not be saved
+      Ok(user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        user.move_password(uuid.as_str(), folder)
+      })?)
+    }
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
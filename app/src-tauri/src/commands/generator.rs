@@ -23,7 +23,7 @@
  * SOFTWARE.
  */
 
-use crate::{model::generator::PasswordGenerator, UserState};
+use crate::{commands::error::CommandError, model::generator::PasswordGenerator, UserState};
 use tauri::{api::path::app_dir, command, AppHandle, State, Wry};
 
 #[command]
@@ -31,27 +31,26 @@ pub fn update_generator(
   state: State<'_, UserState>,
   generator: PasswordGenerator,
   handle: AppHandle<Wry>,
-) -> Result<(), ()> {
+) -> Result<(), CommandError> {
   // get the user
   match &mut *state.0.lock().unwrap() {
     Some(user) => {
-      user.update_generator(generator);
-      // save
-      match user.write(&app_dir(&*handle.config()).unwrap()) {
-        Ok(()) => Ok(()),
-        Err(_) => Err(()),
-      }
+      // undone if it can not be saved
+      Ok(user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        user.update_generator(generator);
+        Ok(())
+      })?)
     }
-    None => Err(()),
+    None => Err(CommandError::NotLoggedIn),
   }
 }
 
 #[command]
-pub fn get_generator(state: State<'_, UserState>) -> Result<PasswordGenerator, ()> {
+pub fn get_generator(state: State<'_, UserState>) -> Result<PasswordGenerator, CommandError> {
   // get the user
   match &mut *state.0.lock().unwrap() {
     Some(user) => Ok(user.generator()),
-    None => Err(()),
+    None => Err(CommandError::NotLoggedIn),
   }
 }
 
@@ -59,10 +58,10 @@ pub fn get_generator(state: State<'_, UserState>) -> Result<PasswordGenerator, (
 pub fn generate_password(
   state: State<'_, UserState>,
   generator: Option<PasswordGenerator>,
-) -> Result<String, ()> {
+) -> Result<String, CommandError> {
   // get the user
   match &mut *state.0.lock().unwrap() {
     Some(user) => Ok(user.generator().generate(generator)),
-    None => Err(()),
+    None => Err(CommandError::NotLoggedIn),
   }
 }
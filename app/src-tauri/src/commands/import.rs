@@ -0,0 +1,71 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{
+  commands::error::CommandError,
+  model::import::{self, ImportFormat, ImportReport},
+  UserState,
+};
+use std::fs;
+use tauri::{api::path::app_dir, command, AppHandle, State, Wry};
+use zeroize::Zeroizing;
+
+/// import the export at the given path, the password and key file are only needed for encrypted
+/// exports
+#[command]
+pub fn import_passwords(
+  format: ImportFormat,
+  path: String,
+  password: Option<String>,
+  key_file: Option<String>,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<ImportReport, CommandError> {
+  let password = password.map(Zeroizing::new);
+  // the export is read and decrypted before the vault gets locked
+  let data = Zeroizing::new(fs::read(path).map_err(import::ImportError::from)?);
+  let key_file = match key_file {
+    Some(key_file) => Some(Zeroizing::new(
+      fs::read(key_file).map_err(import::ImportError::from)?,
+    )),
+    None => None,
+  };
+  let import = import::parse(
+    format,
+    data.as_slice(),
+    password.as_ref().map(|password| password.as_str()),
+    key_file.as_ref().map(|key_file| key_file.as_slice()),
+  )?;
+
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => {
+      let directory = app_dir(&*handle.config()).unwrap();
+      // undone if it can not be saved
+      Ok(user.apply(&directory, |user| user.import_passwords(&directory, import))?)
+    }
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
@@ -23,6 +23,13 @@
  * SOFTWARE.
  */
 
+pub mod attachment;
 pub mod authentication;
+pub mod backup;
+pub mod error;
+pub mod folder;
 pub mod generator;
+pub mod import;
 pub mod password;
+pub mod session;
+pub mod snapshot;
@@ -24,36 +24,66 @@
  */
 
 use crate::{
-  model::user::{AnalyseResult, PasswordData, PasswordType},
+  commands::error::CommandError,
+  model::{
+    history::HistoryEntry,
+    item::ItemKind,
+    otp::OtpCode,
+    search::SearchQuery,
+    user::{AnalyseResult, CorruptEntry, Password, PasswordData, PasswordSummary, PasswordType},
+  },
   UserState,
 };
 use tauri::{api::path::app_dir, command, AppHandle, State, Wry};
 
 #[command]
-pub fn get_passwords(state: State<'_, UserState>) -> Vec<PasswordType> {
+pub fn get_passwords(state: State<'_, UserState>) -> Result<Vec<PasswordType>, CommandError> {
   // get the user
   match &*state.0.lock().unwrap() {
-    Some(user) => user.passwords(),
-    None => Vec::new(),
+    Some(user) => Ok(user.passwords()),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn search_passwords(
+  query: SearchQuery,
+  state: State<'_, UserState>,
+) -> Result<Vec<PasswordSummary>, CommandError> {
+  // get the user
+  match &*state.0.lock().unwrap() {
+    Some(user) => Ok(user.search_passwords(&query)),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn reveal_password(
+  uuid: String,
+  state: State<'_, UserState>,
+) -> Result<Option<String>, CommandError> {
+  // get the user
+  match &*state.0.lock().unwrap() {
+    Some(user) => Ok(user.reveal_password(uuid.as_str())?),
+    None => Err(CommandError::NotLoggedIn),
   }
 }
 
 #[command]
 pub fn new_password(
+  kind: Option<ItemKind>,
   state: State<'_, UserState>,
   handle: AppHandle<Wry>,
-) -> Result<PasswordData, ()> {
+) -> Result<PasswordData, CommandError> {
   // get the user out of the stat
   match &mut *state.0.lock().unwrap() {
     Some(user) => {
-      let data = user.new_password();
-      // save data
-      match user.write(&app_dir(&*handle.config()).unwrap()) {
-        Ok(()) => Ok(data),
-        Err(_) => Err(()),
-      }
+      // logins by default, undone if it can not be saved
+      Ok(user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        Ok(user.new_item(kind.unwrap_or_default()))
+      })?)
     }
-    None => Err(()),
+    None => Err(CommandError::NotLoggedIn),
   }
 }
 
@@ -62,18 +92,16 @@ pub fn update_password(
   data: PasswordData,
   state: State<'_, UserState>,
   handle: AppHandle<Wry>,
-) -> Result<(), ()> {
+) -> Result<(), CommandError> {
   // get the user
   match &mut *state.0.lock().unwrap() {
     Some(user) => {
-      user.update_password(data);
-      // save data
-      match user.write(&app_dir(&*handle.config()).unwrap()) {
-        Ok(()) => Ok(()),
-        Err(_) => Err(()),
-      }
+      // undone if it can not be saved
+      Ok(user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        user.update_password(data)
+      })?)
     }
-    None => Err(()),
+    None => Err(CommandError::NotLoggedIn),
   }
 }
 
@@ -82,34 +110,276 @@ pub fn delete_password(
   data: PasswordData,
   state: State<'_, UserState>,
   handle: AppHandle<Wry>,
-) -> Result<(), ()> {
+) -> Result<(), CommandError> {
   // get the user
   match &mut *state.0.lock().unwrap() {
     Some(user) => {
-      user.delete_password(data);
-      // save data
-      match user.write(&app_dir(&*handle.config()).unwrap()) {
-        Ok(()) => Ok(()),
-        Err(_) => Err(()),
-      }
+      // undone if it can not be saved
+      Ok(user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        user.delete_password(data);
+        Ok(())
+      })?)
     }
-    None => Err(()),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn get_totp(uuid: String, state: State<'_, UserState>) -> Result<OtpCode, CommandError> {
+  // get the user
+  match &*state.0.lock().unwrap() {
+    Some(user) => Ok(user.otp_code(uuid.as_str())?),
+    None => Err(CommandError::NotLoggedIn),
   }
 }
 
 #[command]
-pub fn password_strength(password: String) -> Result<u8, ()> {
+pub fn next_hotp(
+  uuid: String,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<OtpCode, CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => {
+      // undone if it can not be saved
+      Ok(user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        user.next_hotp(uuid.as_str())
+      })?)
+    }
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn get_password_history(
+  uuid: String,
+  state: State<'_, UserState>,
+) -> Result<Vec<HistoryEntry>, CommandError> {
+  // get the user
+  match &*state.0.lock().unwrap() {
+    Some(user) => Ok(user.password_history(uuid.as_str())?),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn restore_password_history(
+  uuid: String,
+  index: usize,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<PasswordData, CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => {
+      // undone if it can not be saved
+      Ok(user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        user.restore_password_history(uuid.as_str(), index)
+      })?)
+    }
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn get_history_retention(state: State<'_, UserState>) -> Result<usize, CommandError> {
+  // get the user
+  match &*state.0.lock().unwrap() {
+    Some(user) => Ok(user.history_retention()),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn update_history_retention(
+  retention: usize,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<(), CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => {
+      // undone if it can not be saved
+      Ok(user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        user.update_history_retention(retention);
+        Ok(())
+      })?)
+    }
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn list_trash(state: State<'_, UserState>) -> Result<Vec<PasswordData>, CommandError> {
+  // get the user
+  match &*state.0.lock().unwrap() {
+    Some(user) => Ok(user.trash()),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn restore_password(
+  uuid: String,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<PasswordData, CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => {
+      // undone if it can not be saved
+      Ok(user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        user.restore_password(uuid.as_str())
+      })?)
+    }
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn purge_trash(
+  uuid: Option<String>,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<(), CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => {
+      // undone if it can not be saved
+      Ok(user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        user.purge_trash(uuid.as_deref());
+        Ok(())
+      })?)
+    }
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn get_trash_retention(state: State<'_, UserState>) -> Result<u64, CommandError> {
+  // get the user
+  match &*state.0.lock().unwrap() {
+    Some(user) => Ok(user.trash_retention()),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn update_trash_retention(
+  retention: u64,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<(), CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => {
+      // undone if it can not be saved
+      Ok(user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        user.update_trash_retention(retention);
+        Ok(())
+      })?)
+    }
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn mark_used(
+  uuid: String,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<(), CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => {
+      // undone if it can not be saved
+      Ok(user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        user.mark_used(uuid.as_str())
+      })?)
+    }
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn get_password_max_age(state: State<'_, UserState>) -> Result<u64, CommandError> {
+  // get the user
+  match &*state.0.lock().unwrap() {
+    Some(user) => Ok(user.password_max_age()),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn update_password_max_age(
+  max_age: u64,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<(), CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => {
+      // undone if it can not be saved
+      Ok(user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        user.update_password_max_age(max_age);
+        Ok(())
+      })?)
+    }
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn password_strength(password: String) -> Result<u8, CommandError> {
   match zxcvbn::zxcvbn(password.as_str(), &[]) {
     Ok(entropy) => Ok(entropy.score()),
-    Err(_) => Err(()),
+    Err(error) => Err(CommandError::InvalidInput(error.to_string())),
   }
 }
 
 #[command]
-pub fn analyse(state: State<'_, UserState>) -> Result<AnalyseResult, ()> {
+pub fn analyse(state: State<'_, UserState>) -> Result<AnalyseResult, CommandError> {
   // get the user
   match &*state.0.lock().unwrap() {
     Some(user) => Ok(user.analyse_passwords()),
-    None => Err(()),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn corrupt_entries(state: State<'_, UserState>) -> Result<Vec<CorruptEntry>, CommandError> {
+  // get the user
+  match &*state.0.lock().unwrap() {
+    Some(user) => Ok(user.corrupt_entries()),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn export_corrupt_entries(state: State<'_, UserState>) -> Result<Vec<Password>, CommandError> {
+  // get the user
+  match &*state.0.lock().unwrap() {
+    Some(user) => Ok(user.export_corrupt_entries()),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn delete_corrupt_entry(
+  id: String,
+  state: State<'_, UserState>,
+  handle: AppHandle<Wry>,
+) -> Result<(), CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => {
+      // undone if it can not be saved
+      Ok(user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        user.delete_corrupt_entry(id.as_str());
+        Ok(())
+      })?)
+    }
+    None => Err(CommandError::NotLoggedIn),
   }
 }
@@ -0,0 +1,90 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{commands::error::CommandError, model::session::AutoLock, SessionState, UserState};
+use std::{thread, time::Duration};
+use tauri::{api::path::app_dir, command, AppHandle, Manager, State, Wry};
+
+/// drop the decrypted vault, but keep the username selected
+pub fn lock_vault(handle: &AppHandle<Wry>) {
+  // wipe the decrypted data, dropping the user releases the file lock too
+  if let Some(mut user) = handle.state::<UserState>().0.lock().unwrap().take() {
+    user.wipe();
+  }
+
+  let state = handle.state::<SessionState>();
+  let mut session = state.0.lock().unwrap();
+  session.lock();
+  // the frontend might not listen yet
+  handle.emit_all("vault-locked", session.username()).ok();
+}
+
+/// lock the vault after inactivity or a suspended system
+pub fn watch(handle: AppHandle<Wry>) {
+  loop {
+    thread::sleep(Duration::from_secs(1));
+
+    let expired = handle.state::<SessionState>().0.lock().unwrap().expired();
+    if expired {
+      lock_vault(&handle);
+    }
+  }
+}
+
+#[command]
+pub fn session_username(state: State<'_, SessionState>) -> Option<String> {
+  state.0.lock().unwrap().username()
+}
+
+#[command]
+pub fn get_auto_lock(state: State<'_, UserState>) -> Result<AutoLock, CommandError> {
+  // get the user
+  match &*state.0.lock().unwrap() {
+    Some(user) => Ok(user.auto_lock()),
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
+
+#[command]
+pub fn update_auto_lock(
+  auto_lock: AutoLock,
+  state: State<'_, UserState>,
+  session: State<'_, SessionState>,
+  handle: AppHandle<Wry>,
+) -> Result<(), CommandError> {
+  // get the user
+  match &mut *state.0.lock().unwrap() {
+    Some(user) => {
+      // undone if it can not be saved
+      user.apply(&app_dir(&*handle.config()).unwrap(), |user| {
+        user.update_auto_lock(auto_lock.clone());
+        Ok(())
+      })?;
+      session.0.lock().unwrap().update(auto_lock);
+      Ok(())
+    }
+    None => Err(CommandError::NotLoggedIn),
+  }
+}
@@ -6,40 +6,100 @@
 mod commands;
 mod model;
 
-use crate::model::user::User;
+use crate::model::{session::Session, user::User};
 use std::{
   fs,
   sync::{Arc, Mutex},
+  thread,
 };
-use tauri::{api::path::app_dir, generate_handler};
+use tauri::{api::path::app_dir, generate_handler, Manager};
 
 pub struct UserState(Arc<Mutex<Option<User>>>);
 
+pub struct SessionState(Arc<Mutex<Session>>);
+
 fn main() {
+  let handler = generate_handler![
+    commands::authentication::login,
+    commands::authentication::signup,
+    commands::authentication::logout,
+    commands::authentication::lock,
+    commands::authentication::unlock,
+    commands::authentication::change_master_password,
+    commands::authentication::rotate_vault_key,
+    commands::authentication::get_private_metadata,
+    commands::authentication::update_private_metadata,
+    commands::password::new_password,
+    commands::password::get_passwords,
+    commands::password::search_passwords,
+    commands::password::reveal_password,
+    commands::password::update_password,
+    commands::password::delete_password,
+    commands::password::mark_used,
+    commands::password::get_password_max_age,
+    commands::password::update_password_max_age,
+    commands::password::list_trash,
+    commands::password::restore_password,
+    commands::password::purge_trash,
+    commands::password::get_trash_retention,
+    commands::password::update_trash_retention,
+    commands::password::get_password_history,
+    commands::password::restore_password_history,
+    commands::password::get_history_retention,
+    commands::password::update_history_retention,
+    commands::password::get_totp,
+    commands::password::next_hotp,
+    commands::password::password_strength,
+    commands::password::analyse,
+    commands::password::corrupt_entries,
+    commands::password::export_corrupt_entries,
+    commands::password::delete_corrupt_entry,
+    commands::attachment::add_attachment,
+    commands::attachment::list_attachments,
+    commands::attachment::download_attachment,
+    commands::attachment::delete_attachment,
+    commands::import::import_passwords,
+    commands::folder::get_folders,
+    commands::folder::new_folder,
+    commands::folder::rename_folder,
+    commands::folder::move_folder,
+    commands::folder::delete_folder,
+    commands::folder::move_password,
+    commands::generator::update_generator,
+    commands::generator::get_generator,
+    commands::generator::generate_password,
+    commands::backup::enable_backup,
+    commands::backup::push_backup,
+    commands::backup::pull_backup,
+    commands::backup::backup_status,
+    commands::snapshot::list_snapshots,
+    commands::snapshot::restore_snapshot,
+    commands::session::session_username,
+    commands::session::get_auto_lock,
+    commands::session::update_auto_lock,
+  ];
+
   tauri::Builder::default()
     .setup(|app| {
       let path = app_dir(&*app.config()).unwrap();
       // create directory if does not exist
       fs::create_dir_all(path.as_path()).unwrap();
 
+      // auto lock in the background
+      let handle = app.handle();
+      thread::spawn(move || commands::session::watch(handle));
+
       Ok(())
     })
     // write empty user into the state
     .manage(UserState(Arc::new(Mutex::new(None))))
-    .invoke_handler(generate_handler![
-      commands::authentication::login,
-      commands::authentication::signup,
-      commands::authentication::logout,
-      commands::password::new_password,
-      commands::password::get_passwords,
-      commands::password::update_password,
-      commands::password::delete_password,
-      commands::password::password_strength,
-      commands::password::analyse,
-      commands::generator::update_generator,
-      commands::generator::get_generator,
-      commands::generator::generate_password,
-    ])
+    .manage(SessionState(Arc::new(Mutex::new(Session::default()))))
+    .invoke_handler(move |invoke| {
+      // every command counts as activity
+      let window = invoke.message.window();
+      window.state::<SessionState>().0.lock().unwrap().touch();
+      handler(invoke)
+    })
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
 }
@@ -0,0 +1,214 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::model::util;
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, io, path::PathBuf};
+use uuid::Uuid;
+use zeroize::Zeroize;
+
+/// the largest file which can be attached, 10 MiB
+pub const MAX_ATTACHMENT_SIZE: usize = 10 * 1024 * 1024;
+
+/// metadata of a file attached to an entry, the content is saved as separate blob
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Attachment {
+  // name of the blob
+  id: String,
+  // original filename
+  name: String,
+  // size of the plain file in bytes
+  size: u64,
+  // unix timestamp in milliseconds
+  created: u64,
+  // base64 key of the blob, only saved inside of the encrypted entry
+  #[serde(default, skip_serializing)]
+  key: String,
+}
+
+impl Attachment {
+  pub fn new(name: String, size: u64, key: String) -> Self {
+    Self {
+      id: Uuid::new_v4().to_string(),
+      name,
+      size,
+      created: util::now(),
+      key,
+    }
+  }
+
+  /// copy of the metadata under a new id, the key is set with the new blob
+  pub fn renew(&self) -> Self {
+    Self {
+      id: Uuid::new_v4().to_string(),
+      name: self.name.clone(),
+      size: self.size,
+      created: self.created,
+      key: String::new(),
+    }
+  }
+
+  pub fn id(&self) -> &str {
+    self.id.as_str()
+  }
+
+  pub fn key(&self) -> &str {
+    self.key.as_str()
+  }
+
+  pub fn set_key(&mut self, key: String) {
+    self.key.zeroize();
+    self.key = key;
+  }
+}
+
+impl Zeroize for Attachment {
+  fn zeroize(&mut self) {
+    self.key.zeroize();
+  }
+}
+
+impl Drop for Attachment {
+  fn drop(&mut self) {
+    self.zeroize();
+  }
+}
+
+impl fmt::Debug for Attachment {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Attachment")
+      .field("id", &self.id)
+      .field("name", &self.name)
+      .field("size", &self.size)
+      .field("created", &self.created)
+      .field("key", &"<redacted>")
+      .finish()
+  }
+}
+
+/// encrypted blobs of the attachments
+pub struct Attachments {
+  directory: PathBuf,
+}
+
+impl Attachments {
+  /// the attachments of the user are stored in `attachments/<username>`
+  pub fn new(directory: &PathBuf, username: &str) -> Self {
+    Self {
+      directory: directory.join("attachments").join(username.to_lowercase()),
+    }
+  }
+
+  /// save an encrypted blob
+  pub fn write(&self, id: &str, data: &[u8]) -> io::Result<()> {
+    fs::create_dir_all(&self.directory)?;
+    // write a temporary file first, so an existing blob never gets truncated
+    let temp = self.path(id)?.with_extension("tmp");
+    fs::write(&temp, data)?;
+    fs::rename(&temp, self.path(id)?)
+  }
+
+  /// read an encrypted blob
+  pub fn read(&self, id: &str) -> io::Result<Vec<u8>> {
+    fs::read(self.path(id)?)
+  }
+
+  /// list the ids of all saved blobs
+  pub fn list(&self) -> io::Result<Vec<String>> {
+    if !self.directory.exists() {
+      return Ok(Vec::new());
+    }
+
+    Ok(
+      fs::read_dir(&self.directory)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        // skip the temporary files
+        .filter(|id| Uuid::parse_str(id).is_ok())
+        .collect(),
+    )
+  }
+
+  /// remove a blob, missing blobs are ignored
+  pub fn remove(&self, id: &str) -> io::Result<()> {
+    match fs::remove_file(self.path(id)?) {
+      Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+      _ => Ok(()),
+    }
+  }
+
+  fn path(&self, id: &str) -> io::Result<PathBuf> {
+    // the id is used as filename
+    match Uuid::parse_str(id) {
+      Ok(_) => Ok(self.directory.join(id)),
+      Err(_) => Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "Unknown attachment",
+      )),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  #[test]
+  fn test_blobs() {
+    let dir = TempDir::new().unwrap();
+    let attachments = Attachments::new(&dir.as_ref().to_path_buf(), "Username");
+    let attachment = Attachment::new(String::from("cert.pem"), 4, String::from("key"));
+
+    attachments.write(attachment.id(), b"data").unwrap();
+    assert!(dir
+      .as_ref()
+      .join("attachments/username")
+      .join(attachment.id())
+      .exists());
+    assert_eq!(b"data".to_vec(), attachments.read(attachment.id()).unwrap());
+
+    fs::write(dir.as_ref().join("attachments/username/other.tmp"), b"").unwrap();
+    assert_eq!(
+      vec![attachment.id().to_string()],
+      attachments.list().unwrap()
+    );
+
+    attachments.remove(attachment.id()).unwrap();
+    assert!(attachments.read(attachment.id()).is_err());
+    // removing twice is fine
+    attachments.remove(attachment.id()).unwrap();
+  }
+
+  #[test]
+  fn test_invalid_id() {
+    let dir = TempDir::new().unwrap();
+    let attachments = Attachments::new(&dir.as_ref().to_path_buf(), "username");
+
+    assert!(attachments.read("../username.json").is_err());
+    assert!(attachments.write("", b"data").is_err());
+    assert!(attachments.remove("../username.json").is_err());
+  }
+}
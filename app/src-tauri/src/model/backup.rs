@@ -23,10 +23,15 @@
  * SOFTWARE.
  */
 
-use crate::model::encryption::{Encryption, EncryptionError};
+use crate::model::{
+  encryption::{Encryption, EncryptionError},
+  sync::{SyncClient, SyncError},
+};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use zeroize::Zeroize;
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 pub struct Backup {
   // the uuid will be encrypted with the user password for better security on the local disk
   // will be available in plaintext here, not sure how secure that is
@@ -34,9 +39,54 @@ pub struct Backup {
   enabled: bool,
   // the aes iv
   iv: String,
+  // base url of the worker
+  #[serde(default)]
+  url: String,
+  // password of the backup account, encrypted like the uuid
+  #[serde(default)]
+  password: String,
+  // the aes iv of the password
+  #[serde(default)]
+  password_iv: String,
+}
+
+impl Drop for Backup {
+  fn drop(&mut self) {
+    self.password.zeroize();
+  }
+}
+
+impl fmt::Debug for Backup {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Backup")
+      .field("uuid", &self.uuid)
+      .field("enabled", &self.enabled)
+      .field("url", &self.url)
+      .field("password", &"<redacted>")
+      .finish()
+  }
+}
+
+#[derive(Deserialize, Serialize, Default)]
+pub struct BackupStatus {
+  enabled: bool,
+  uuid: Option<String>,
+  url: Option<String>,
 }
 
 impl Backup {
+  /// create new enabled backup from plaintext credentials
+  pub fn new(url: String, uuid: String, password: String) -> Self {
+    Self {
+      uuid,
+      enabled: true,
+      iv: String::new(),
+      url,
+      password,
+      password_iv: String::new(),
+    }
+  }
+
   pub fn uuid(self) -> String {
     self.uuid.clone()
   }
@@ -49,7 +99,46 @@ impl Backup {
   pub fn init_from_login(mut self, encryption: &Encryption) -> Result<Self, EncryptionError> {
     // decrypt uuid
     self.uuid = encryption.decrypt(self.uuid.clone(), self.iv.clone())?;
+    // backups created before the sync have no password
+    if !self.password_iv.is_empty() {
+      self.password = encryption.decrypt(self.password.clone(), self.password_iv.clone())?;
+    }
     // return the updated version
     Ok(self)
   }
+
+  /// encrypt the credentials for the storage on the disk
+  pub fn encrypt(&self, encryption: &Encryption) -> Result<Self, EncryptionError> {
+    let uuid = encryption.encrypt(self.uuid.as_str())?;
+    let password = encryption.encrypt(self.password.as_str())?;
+
+    Ok(Self {
+      uuid: uuid.ciphertext,
+      enabled: self.enabled,
+      iv: uuid.nonce,
+      url: self.url.clone(),
+      password: password.ciphertext,
+      password_iv: password.nonce,
+    })
+  }
+
+  /// start a new session on the worker
+  pub fn client(&self) -> Result<SyncClient, SyncError> {
+    if !self.enabled {
+      return Err(SyncError::Unauthorized);
+    }
+
+    let mut client = SyncClient::new(self.url.as_str());
+    client.login(self.uuid.as_str(), self.password.as_str())?;
+    Ok(client)
+  }
+
+  /// get the current status without the password
+  pub fn status(&self) -> BackupStatus {
+    BackupStatus {
+      enabled: self.enabled,
+      uuid: Some(self.uuid.clone()),
+      url: Some(self.url.clone()),
+    }
+  }
 }
@@ -29,6 +29,7 @@ use aes_gcm_siv::{
 };
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use zeroize::Zeroizing;
 
 #[derive(Error, Debug)]
 pub enum EncryptionError {
@@ -37,14 +38,31 @@ pub enum EncryptionError {
 
   #[error(transparent)]
   AESError(#[from] aes_gcm_siv::aead::Error),
+
+  #[error(transparent)]
+  Base64Error(#[from] base64::DecodeError),
+
+  #[error("Invalid nonce")]
+  NonceError,
+
+  #[error("Invalid key")]
+  KeyError,
 }
 
 type Result<T> = std::result::Result<T, EncryptionError>;
 
+/// version tag of the binary nonce format
+///
+/// the legacy format used the ascii bytes of 8 base64 encoded random bytes as nonce
+const NONCE_VERSION: u8 = 2;
+/// aes-gcm-siv uses 96 bit nonces
+const NONCE_LENGTH: usize = 12;
+
 /// struct for general encryption and decryption of the data
 #[derive(Clone)]
 pub struct Encryption {
-  cipher: Aes256GcmSiv,
+  // the cipher is built for every operation, so only the key has to be wiped
+  key: Zeroizing<[u8; 32]>,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -66,42 +84,121 @@ impl Encryption {
 
   /// create new from bytes
   pub fn new(key: &[u8]) -> Self {
-    let key = Key::from_slice(key);
-    // build cipher
-    let cipher = Aes256GcmSiv::new(key);
+    let mut bytes = Zeroizing::new([0u8; 32]);
+    bytes.copy_from_slice(key);
+
+    Self { key: bytes }
+  }
+
+  /// build the cipher of the key
+  fn cipher(&self) -> Aes256GcmSiv {
+    Aes256GcmSiv::new(Key::from_slice(self.key.as_ref()))
+  }
+
+  /// create new from a base64 encoded 256 bit key
+  pub fn from_base64(key: &str) -> Result<Self> {
+    let key = Zeroizing::new(base64::decode(key)?);
+    match key.len() {
+      32 => Ok(Self::new(key.as_slice())),
+      _ => Err(EncryptionError::KeyError),
+    }
+  }
+
+  /// check whether the nonce has been created in the legacy format
+  pub fn is_legacy(iv: &str) -> bool {
+    Self::tagged_nonce(iv).is_none()
+  }
+
+  /// get the raw nonce of the versioned binary format
+  fn tagged_nonce(iv: &str) -> Option<Vec<u8>> {
+    match base64::decode(iv) {
+      Ok(bytes) if bytes.len() == NONCE_LENGTH + 1 && bytes[0] == NONCE_VERSION => {
+        Some(bytes[1..].to_vec())
+      }
+      _ => None,
+    }
+  }
+
+  /// parse the raw nonce of any format
+  fn nonce(iv: &str) -> Result<Vec<u8>> {
+    // legacy nonces are the ascii bytes of the string
+    let nonce = Self::tagged_nonce(iv).unwrap_or_else(|| iv.as_bytes().to_vec());
 
-    Self { cipher }
+    match nonce.len() {
+      NONCE_LENGTH => Ok(nonce),
+      _ => Err(EncryptionError::NonceError),
+    }
   }
 
   /// decrypt a &str into a String
   pub fn decrypt(&self, data: String, iv: String) -> Result<String> {
     // parse nonce and ciphertext from data
     // create nonce as array
-    let nonce = Nonce::from_slice(iv.as_bytes());
+    let nonce = Self::nonce(iv.as_str())?;
+    let nonce = Nonce::from_slice(nonce.as_slice());
     // decrypt
     let plaintext = self
-      .cipher
-      .decrypt(&nonce, base64::decode(data).unwrap().as_slice())?;
-    let parsed = String::from_utf8(plaintext.clone()).unwrap_or(base64::encode(plaintext));
+      .cipher()
+      .decrypt(&nonce, base64::decode(data)?.as_slice())?;
+    // avoid leaving a copy of the plaintext behind
+    let parsed = match String::from_utf8(plaintext) {
+      Ok(parsed) => parsed,
+      Err(error) => base64::encode(Zeroizing::new(error.into_bytes()).as_slice()),
+    };
     Ok(parsed)
   }
 
   /// encrypt data with random nonce
   pub fn encrypt(&self, data: &str) -> Result<CipherText> {
     // generate nonce
-    let nonce_string = Self::generate(8);
-    let nonce = Nonce::from_slice(nonce_string.as_bytes());
+    let nonce = (0..NONCE_LENGTH)
+      .map(|_| rand::random::<u8>())
+      .collect::<Vec<u8>>();
 
     // encrypt
-    let ciphertext = self.cipher.encrypt(&nonce, data.as_bytes())?;
+    let ciphertext = self
+      .cipher()
+      .encrypt(Nonce::from_slice(nonce.as_slice()), data.as_bytes())?;
     let ciphertext = base64::encode(ciphertext);
 
+    // prefix the nonce with the version tag
+    let mut tagged = vec![NONCE_VERSION];
+    tagged.extend(nonce);
+
     // return nonce and ciphertext
     Ok(CipherText {
       ciphertext,
-      nonce: nonce_string,
+      nonce: base64::encode(tagged),
     })
   }
+
+  /// encrypt binary data, the tagged nonce is prepended to the ciphertext
+  pub fn encrypt_bytes(&self, data: &[u8]) -> Result<Vec<u8>> {
+    let nonce = (0..NONCE_LENGTH)
+      .map(|_| rand::random::<u8>())
+      .collect::<Vec<u8>>();
+
+    let mut encrypted = vec![NONCE_VERSION];
+    encrypted.extend(nonce.iter());
+    encrypted.extend(
+      self
+        .cipher()
+        .encrypt(Nonce::from_slice(nonce.as_slice()), data)?,
+    );
+    Ok(encrypted)
+  }
+
+  /// decrypt binary data created by `encrypt_bytes`
+  pub fn decrypt_bytes(&self, data: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    if data.len() < NONCE_LENGTH + 1 || data[0] != NONCE_VERSION {
+      return Err(EncryptionError::NonceError);
+    }
+
+    let nonce = Nonce::from_slice(&data[1..NONCE_LENGTH + 1]);
+    Ok(Zeroizing::new(
+      self.cipher().decrypt(nonce, &data[NONCE_LENGTH + 1..])?,
+    ))
+  }
 }
 
 #[cfg(test)]
@@ -113,6 +210,26 @@ mod tests {
   };
   use rand::rngs::OsRng;
 
+  #[test]
+  fn test_bytes() {
+    let encryption = Encryption::from_base64(Encryption::generate(32).as_str()).unwrap();
+    let data = vec![0, 159, 146, 150, 255];
+
+    let encrypted = encryption.encrypt_bytes(data.as_slice()).unwrap();
+    assert_eq!(
+      data,
+      encryption
+        .decrypt_bytes(encrypted.as_slice())
+        .unwrap()
+        .to_vec()
+    );
+    // wrong key
+    let other = Encryption::from_base64(Encryption::generate(32).as_str()).unwrap();
+    assert!(other.decrypt_bytes(encrypted.as_slice()).is_err());
+    // truncated
+    assert!(encryption.decrypt_bytes(&encrypted[..5]).is_err());
+  }
+
   #[test]
   fn test_iv_length() {
     let iv = Encryption::generate(8);
@@ -145,4 +262,61 @@ mod tests {
       .unwrap();
     assert_eq!(plaintext, "hello")
   }
+
+  #[test]
+  fn test_nonce_format() {
+    let encryption = Encryption::new(&[0; 32]);
+    let ciphertext = encryption.encrypt("hello").unwrap();
+
+    let nonce = base64::decode(ciphertext.nonce.as_str()).unwrap();
+    assert_eq!(nonce.len(), 13);
+    assert_eq!(nonce[0], NONCE_VERSION);
+    assert!(!Encryption::is_legacy(ciphertext.nonce.as_str()));
+  }
+
+  #[test]
+  fn test_legacy_decryption() {
+    let encryption = Encryption::new(&[0; 32]);
+    // encrypt like before the binary nonces
+    let iv = Encryption::generate(8);
+    let ciphertext = encryption
+      .cipher()
+      .encrypt(Nonce::from_slice(iv.as_bytes()), "hello".as_bytes())
+      .unwrap();
+
+    assert!(Encryption::is_legacy(iv.as_str()));
+    let plaintext = encryption.decrypt(base64::encode(ciphertext), iv).unwrap();
+    assert_eq!(plaintext, "hello")
+  }
+
+  #[test]
+  fn test_invalid_nonce() {
+    let encryption = Encryption::new(&[0; 32]);
+    let ciphertext = encryption.encrypt("hello").unwrap();
+
+    assert!(matches!(
+      encryption.decrypt(ciphertext.ciphertext, String::from("short")),
+      Err(EncryptionError::NonceError)
+    ));
+  }
+
+  #[test]
+  fn test_invalid_data() {
+    let encryption = Encryption::new(&[0; 32]);
+    let ciphertext = encryption.encrypt("hello").unwrap();
+
+    assert!(matches!(
+      encryption.decrypt(String::from("#"), ciphertext.nonce),
+      Err(EncryptionError::Base64Error(_))
+    ));
+  }
+
+  #[test]
+  fn test_invalid_key() {
+    assert!(Encryption::from_base64(Encryption::generate(32).as_str()).is_ok());
+    assert!(matches!(
+      Encryption::from_base64(Encryption::generate(16).as_str()),
+      Err(EncryptionError::KeyError)
+    ));
+  }
 }
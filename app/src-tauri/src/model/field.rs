@@ -0,0 +1,201 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::model::util;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use thiserror::Error;
+use zeroize::Zeroize;
+
+#[derive(Error, Debug)]
+pub enum FieldError {
+  #[error("Invalid value for the field {0}")]
+  ValueError(String),
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldKind {
+  Text,
+  // never added to the search index
+  Hidden,
+  // absolute with a scheme and a host
+  Url,
+  // "true" or "false"
+  Boolean,
+  // YYYY-MM-DD
+  Date,
+}
+
+/// user defined field of an entry
+#[derive(Deserialize, Serialize, Clone)]
+pub struct CustomField {
+  label: String,
+  value: String,
+  kind: FieldKind,
+}
+
+impl CustomField {
+  pub fn new(label: String, value: String, kind: FieldKind) -> Self {
+    Self { label, value, kind }
+  }
+
+  pub fn label(&self) -> &str {
+    self.label.as_str()
+  }
+
+  /// the value if it may be searched in plaintext
+  pub fn searchable_value(&self) -> Option<&str> {
+    match self.kind {
+      FieldKind::Hidden => None,
+      _ => Some(self.value.as_str()),
+    }
+  }
+
+  /// check the value against the kind, empty values are always allowed
+  pub fn validate(&self) -> Result<(), FieldError> {
+    let valid = self.value.is_empty()
+      || match self.kind {
+        FieldKind::Boolean => self.value.eq("true") || self.value.eq("false"),
+        FieldKind::Date => is_date(self.value.as_str()),
+        FieldKind::Url => is_url(self.value.as_str()),
+        _ => true,
+      };
+
+    match valid {
+      true => Ok(()),
+      false => Err(FieldError::ValueError(self.label.clone())),
+    }
+  }
+
+  /// keep the value as plain text if it does not match the kind, e.g. for imported fields
+  pub fn or_text(mut self) -> Self {
+    if self.validate().is_err() {
+      self.kind = FieldKind::Text;
+    }
+    self
+  }
+}
+
+/// check for a YYYY-MM-DD date
+fn is_date(value: &str) -> bool {
+  let parts = value.split('-').collect::<Vec<&str>>();
+  if parts.len() != 3
+    || parts[0].len() != 4
+    || parts[1].len() != 2
+    || parts[2].len() != 2
+    || !parts
+      .iter()
+      .all(|part| part.chars().all(|c| c.is_ascii_digit()))
+  {
+    return false;
+  }
+
+  let year = parts[0].parse::<i64>().unwrap();
+  let month = parts[1].parse::<u32>().unwrap();
+  let day = parts[2].parse::<u32>().unwrap();
+  util::days_from_civil(year, month, day).is_some()
+}
+
+/// an absolute url with a scheme and a host
+fn is_url(value: &str) -> bool {
+  url::Url::parse(value)
+    .ok()
+    .and_then(|url| url.host_str().map(|host| !host.is_empty()))
+    .unwrap_or(false)
+}
+
+impl Zeroize for CustomField {
+  fn zeroize(&mut self) {
+    self.label.zeroize();
+    self.value.zeroize();
+  }
+}
+
+// the value is always redacted
+impl fmt::Debug for CustomField {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("CustomField")
+      .field("label", &self.label)
+      .field("value", &"<redacted>")
+      .field("kind", &self.kind)
+      .finish()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_validate() {
+    let field =
+      |value: &str, kind| CustomField::new(String::from("field"), value.to_string(), kind);
+
+    assert!(field("true", FieldKind::Boolean).validate().is_ok());
+    assert!(field("yes", FieldKind::Boolean).validate().is_err());
+    assert!(field("2022-02-28", FieldKind::Date).validate().is_ok());
+    assert!(field("2022-13-01", FieldKind::Date).validate().is_err());
+    assert!(field("2022-02-29", FieldKind::Date).validate().is_err());
+    assert!(field("2022-04-31", FieldKind::Date).validate().is_err());
+    assert!(field("2022-02-00", FieldKind::Date).validate().is_err());
+    assert!(field("2024-02-29", FieldKind::Date).validate().is_ok());
+    assert!(field("2000-02-29", FieldKind::Date).validate().is_ok());
+    assert!(field("1900-02-29", FieldKind::Date).validate().is_err());
+    assert!(field("28.02.2022", FieldKind::Date).validate().is_err());
+    assert!(field("", FieldKind::Date).validate().is_ok());
+    assert!(field("anything", FieldKind::Hidden).validate().is_ok());
+    assert!(field("https://example.com/login", FieldKind::Url)
+      .validate()
+      .is_ok());
+    assert!(field("example.com", FieldKind::Url).validate().is_err());
+    assert!(field("mailto:alice", FieldKind::Url).validate().is_err());
+  }
+
+  #[test]
+  fn test_or_text() {
+    let date = CustomField::new(
+      String::from("birthday"),
+      String::from("17.05.1990"),
+      FieldKind::Date,
+    )
+    .or_text();
+    assert!(matches!(date.kind, FieldKind::Text));
+    assert!(date.validate().is_ok());
+
+    let valid = CustomField::new(String::from("pin"), String::from("1234"), FieldKind::Hidden);
+    assert!(matches!(valid.or_text().kind, FieldKind::Hidden));
+  }
+
+  #[test]
+  fn test_hidden() {
+    let hidden = CustomField::new(String::from("pin"), String::from("1234"), FieldKind::Hidden);
+    assert_eq!(None, hidden.searchable_value());
+    assert!(!format!("{:?}", hidden).contains("1234"));
+
+    let text = CustomField::new(String::from("account"), String::from("42"), FieldKind::Text);
+    assert_eq!(Some("42"), text.searchable_value());
+  }
+}
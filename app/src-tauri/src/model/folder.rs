@@ -0,0 +1,124 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use zeroize::Zeroize;
+
+/// folder for the passwords, nested by the uuid of the parent
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Folder {
+  // identification
+  uuid: String,
+  name: String,
+  // none for top level folders
+  parent: Option<String>,
+}
+
+impl Folder {
+  /// create new folder with a random uuid
+  pub fn new(name: String, parent: Option<String>) -> Self {
+    Self {
+      uuid: Uuid::new_v4().to_string(),
+      name,
+      parent,
+    }
+  }
+
+  pub fn uuid(&self) -> &str {
+    self.uuid.as_str()
+  }
+
+  pub fn name(&self) -> &str {
+    self.name.as_str()
+  }
+
+  pub fn parent(&self) -> Option<&String> {
+    self.parent.as_ref()
+  }
+
+  pub fn rename(&mut self, name: String) {
+    self.name = name
+  }
+
+  pub fn set_parent(&mut self, parent: Option<String>) {
+    self.parent = parent
+  }
+}
+
+/// get the uuids of the folder and all of its subfolders
+pub fn subtree(folders: &[Folder], uuid: &str) -> Vec<String> {
+  let mut result = vec![uuid.to_string()];
+  let mut index = 0;
+  while index < result.len() {
+    let children = folders
+      .iter()
+      .filter(|folder| folder.parent.as_ref().eq(&Some(&result[index])))
+      // skip already visited folders, so broken data can not loop forever
+      .filter(|folder| !result.contains(&folder.uuid))
+      .map(|folder| folder.uuid.clone())
+      .collect::<Vec<String>>();
+    result.extend(children);
+    index += 1;
+  }
+
+  result
+}
+
+// the names are only saved encrypted
+impl Zeroize for Folder {
+  fn zeroize(&mut self) {
+    self.name.zeroize();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_subtree() {
+    let root = Folder::new(String::from("root"), None);
+    let child = Folder::new(String::from("child"), Some(root.uuid.clone()));
+    let nested = Folder::new(String::from("nested"), Some(child.uuid.clone()));
+    let other = Folder::new(String::from("other"), None);
+    let folders = vec![root.clone(), child.clone(), nested.clone(), other];
+
+    assert_eq!(
+      vec![root.uuid.clone(), child.uuid.clone(), nested.uuid.clone()],
+      subtree(&folders, root.uuid())
+    );
+    assert_eq!(vec![nested.uuid.clone()], subtree(&folders, nested.uuid()));
+  }
+
+  #[test]
+  fn test_subtree_cycle() {
+    let mut first = Folder::new(String::from("first"), None);
+    let second = Folder::new(String::from("second"), Some(first.uuid.clone()));
+    first.set_parent(Some(second.uuid.clone()));
+
+    assert_eq!(2, subtree(&[first.clone(), second], first.uuid()).len());
+  }
+}
@@ -0,0 +1,79 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::model::util;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use zeroize::Zeroize;
+
+/// how many previous passwords are kept by default
+pub const DEFAULT_RETENTION: usize = 10;
+
+pub fn default_retention() -> usize {
+  DEFAULT_RETENTION
+}
+
+/// previous password of an entry
+#[derive(Deserialize, Serialize, Clone)]
+pub struct HistoryEntry {
+  password: String,
+  // unix timestamp in milliseconds, when the password got replaced
+  changed: u64,
+}
+
+impl HistoryEntry {
+  pub fn new(password: String) -> Self {
+    Self {
+      password,
+      changed: util::now(),
+    }
+  }
+
+  pub fn password(&self) -> &String {
+    &self.password
+  }
+}
+
+impl Zeroize for HistoryEntry {
+  fn zeroize(&mut self) {
+    self.password.zeroize();
+  }
+}
+
+impl Drop for HistoryEntry {
+  fn drop(&mut self) {
+    self.zeroize();
+  }
+}
+
+// the password is always redacted
+impl fmt::Debug for HistoryEntry {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("HistoryEntry")
+      .field("password", &"<redacted>")
+      .field("changed", &self.changed)
+      .finish()
+  }
+}
@@ -0,0 +1,538 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::model::{
+  field::{CustomField, FieldKind},
+  import::{non_empty, timestamp, Import, ImportError, ImportedEntry, Result},
+  item::{Card, Identity, Item, Note},
+};
+use aes::Aes256;
+use argon2::{Algorithm, Argon2, Params, Version};
+use cbc::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use zeroize::Zeroizing;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Export {
+  #[serde(default)]
+  encrypted: bool,
+  #[serde(default)]
+  password_protected: bool,
+  // the kdf parameters of password protected exports
+  salt: Option<String>,
+  kdf_type: Option<u8>,
+  kdf_iterations: Option<u32>,
+  // MiB
+  kdf_memory: Option<u32>,
+  kdf_parallelism: Option<u32>,
+  #[serde(rename = "encKeyValidation_DO_NOT_EDIT")]
+  validation: Option<String>,
+  // the encrypted plain export
+  data: Option<String>,
+  #[serde(default)]
+  folders: Vec<Folder>,
+  // parsed one by one, so a single broken item does not fail the import
+  #[serde(default)]
+  items: Vec<Value>,
+}
+
+#[derive(Deserialize)]
+struct Folder {
+  id: String,
+  name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Cipher {
+  #[serde(rename = "type")]
+  kind: u8,
+  name: Option<String>,
+  notes: Option<String>,
+  #[serde(default)]
+  favorite: bool,
+  folder_id: Option<String>,
+  fields: Option<Vec<Field>>,
+  login: Option<Login>,
+  card: Option<CipherCard>,
+  identity: Option<CipherIdentity>,
+  creation_date: Option<String>,
+  revision_date: Option<String>,
+  password_history: Option<Vec<History>>,
+}
+
+#[derive(Deserialize)]
+struct Field {
+  name: Option<String>,
+  value: Option<String>,
+  // 0 text, 1 hidden, 2 boolean, 3 linked
+  #[serde(rename = "type")]
+  kind: u8,
+}
+
+#[derive(Deserialize)]
+struct Login {
+  uris: Option<Vec<Uri>>,
+  username: Option<String>,
+  password: Option<String>,
+  totp: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Uri {
+  uri: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CipherCard {
+  cardholder_name: Option<String>,
+  brand: Option<String>,
+  number: Option<String>,
+  exp_month: Option<String>,
+  exp_year: Option<String>,
+  code: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CipherIdentity {
+  title: Option<String>,
+  first_name: Option<String>,
+  middle_name: Option<String>,
+  last_name: Option<String>,
+  address1: Option<String>,
+  address2: Option<String>,
+  address3: Option<String>,
+  city: Option<String>,
+  state: Option<String>,
+  postal_code: Option<String>,
+  country: Option<String>,
+  company: Option<String>,
+  email: Option<String>,
+  phone: Option<String>,
+  ssn: Option<String>,
+  username: Option<String>,
+  passport_number: Option<String>,
+  license_number: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct History {
+  password: Option<String>,
+}
+
+/// read a plain or password protected json export of bitwarden
+///
+/// exports encrypted with the account key can only be read by bitwarden itself
+pub fn parse(json: &str, password: Option<&str>) -> Result<Import> {
+  let export = serde_json::from_str::<Export>(json)?;
+  if !export.encrypted {
+    return Ok(convert(export));
+  }
+  if !export.password_protected {
+    return Err(ImportError::Unsupported(String::from(
+      "Exports encrypted with the account key are not supported, use a password protected export",
+    )));
+  }
+
+  let content = decrypt(&export, password.ok_or(ImportError::PasswordRequired)?)?;
+  let inner = serde_json::from_str::<Export>(content.as_str())?;
+  Ok(convert(inner))
+}
+
+/// derive the keys from the password and decrypt the wrapped plain export
+fn decrypt(export: &Export, password: &str) -> Result<Zeroizing<String>> {
+  let missing = |field: &str| ImportError::FormatError(format!("{} is missing", field));
+  let salt = export.salt.as_deref().ok_or_else(|| missing("salt"))?;
+  let iterations = export
+    .kdf_iterations
+    .ok_or_else(|| missing("kdfIterations"))?;
+
+  let mut key = Zeroizing::new([0u8; 32]);
+  match export.kdf_type.unwrap_or(0) {
+    0 => {
+      pbkdf2::pbkdf2::<Hmac<Sha256>>(password.as_bytes(), salt.as_bytes(), iterations, &mut *key)
+    }
+    1 => {
+      let params = Params::new(
+        export
+          .kdf_memory
+          .ok_or_else(|| missing("kdfMemory"))?
+          .checked_mul(1024)
+          .ok_or_else(|| ImportError::FormatError(String::from("kdfMemory is too large")))?,
+        iterations,
+        export
+          .kdf_parallelism
+          .ok_or_else(|| missing("kdfParallelism"))?,
+        Some(32),
+      )
+      .map_err(|error| ImportError::FormatError(error.to_string()))?;
+      // argon2 gets the hash of the salt
+      Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(
+          password.as_bytes(),
+          Sha256::digest(salt.as_bytes()).as_slice(),
+          &mut *key,
+        )
+        .map_err(|error| ImportError::FormatError(error.to_string()))?;
+    }
+    kdf => return Err(ImportError::Unsupported(format!("Unknown kdf {}", kdf))),
+  }
+
+  // stretch into separate keys for aes and the mac
+  let hkdf = Hkdf::<Sha256>::from_prk(&*key).map_err(|_| ImportError::DecryptError)?;
+  let mut encryption = Zeroizing::new([0u8; 32]);
+  let mut mac = Zeroizing::new([0u8; 32]);
+  hkdf
+    .expand(b"enc", &mut *encryption)
+    .and_then(|_| hkdf.expand(b"mac", &mut *mac))
+    .map_err(|_| ImportError::DecryptError)?;
+
+  // fails for a wrong password, before the data gets touched
+  let validation = export
+    .validation
+    .as_deref()
+    .ok_or_else(|| missing("encKeyValidation_DO_NOT_EDIT"))?;
+  decrypt_string(validation, &*encryption, &*mac)?;
+  decrypt_string(
+    export.data.as_deref().ok_or_else(|| missing("data"))?,
+    &*encryption,
+    &*mac,
+  )
+}
+
+/// decrypt an encrypted string of the type 2, `2.<iv>|<ciphertext>|<mac>`
+fn decrypt_string(value: &str, encryption: &[u8], mac: &[u8]) -> Result<Zeroizing<String>> {
+  let value = value.strip_prefix("2.").ok_or_else(|| {
+    ImportError::Unsupported(String::from("Only AES-CBC with HMAC-SHA256 is supported"))
+  })?;
+  let parts = value
+    .split('|')
+    .map(base64::decode)
+    .collect::<std::result::Result<Vec<Vec<u8>>, base64::DecodeError>>()
+    .map_err(|error| ImportError::FormatError(error.to_string()))?;
+  if parts.len() != 3 {
+    return Err(ImportError::FormatError(String::from(
+      "invalid encrypted string",
+    )));
+  }
+
+  let mut hmac = Hmac::<Sha256>::new_from_slice(mac).map_err(|_| ImportError::DecryptError)?;
+  hmac.update(parts[0].as_slice());
+  hmac.update(parts[1].as_slice());
+  hmac
+    .verify_slice(parts[2].as_slice())
+    .map_err(|_| ImportError::DecryptError)?;
+
+  let plaintext = Zeroizing::new(
+    cbc::Decryptor::<Aes256>::new_from_slices(encryption, parts[0].as_slice())
+      .map_err(|_| ImportError::DecryptError)?
+      .decrypt_padded_vec_mut::<Pkcs7>(parts[1].as_slice())
+      .map_err(|_| ImportError::DecryptError)?,
+  );
+  match std::str::from_utf8(plaintext.as_slice()) {
+    Ok(plaintext) => Ok(Zeroizing::new(plaintext.to_string())),
+    Err(_) => Err(ImportError::DecryptError),
+  }
+}
+
+/// map the items of a plain export
+fn convert(export: Export) -> Import {
+  // nested folders are named like `parent/child`
+  let folders = export
+    .folders
+    .iter()
+    .map(|folder| (folder.id.as_str(), folder.name.as_str()))
+    .collect::<HashMap<&str, &str>>();
+
+  let mut import = Import::default();
+  for (index, item) in export.items.into_iter().enumerate() {
+    let position = index + 1;
+    let name = item["name"].as_str().map(str::to_string);
+    let cipher = match serde_json::from_value::<Cipher>(item) {
+      Ok(cipher) => cipher,
+      Err(error) => {
+        import.skip(position, name, error.to_string().as_str());
+        continue;
+      }
+    };
+
+    let mut entry = ImportedEntry::default();
+    entry.item = match cipher.kind {
+      1 => Item::Login,
+      2 => Item::Note(Note::new(non_empty(cipher.notes.as_deref()))),
+      3 => Item::Card(card(cipher.card.as_ref())),
+      4 => Item::Identity(identity(cipher.identity.as_ref(), &mut entry.fields)),
+      _ => {
+        import.skip(position, name, "unsupported item type");
+        continue;
+      }
+    };
+    entry.name = non_empty(cipher.name.as_deref());
+    // the notes of secure notes are the content
+    if cipher.kind != 2 {
+      entry.description = non_empty(cipher.notes.as_deref());
+    }
+    entry.favorite = cipher.favorite;
+    entry.folder = cipher
+      .folder_id
+      .as_deref()
+      .and_then(|id| folders.get(id))
+      .map(|name| {
+        name
+          .split('/')
+          .filter(|name| !name.trim().is_empty())
+          .map(|name| name.trim().to_string())
+          .collect()
+      })
+      .unwrap_or_default();
+    entry.created = cipher.creation_date.as_deref().and_then(timestamp);
+    entry.modified = cipher.revision_date.as_deref().and_then(timestamp);
+
+    if let Some(login) = &cipher.login {
+      entry.login = non_empty(login.username.as_deref());
+      entry.password = non_empty(login.password.as_deref());
+      entry.otp = non_empty(login.totp.as_deref());
+      let mut uris = login
+        .uris
+        .iter()
+        .flatten()
+        .filter_map(|uri| non_empty(uri.uri.as_deref()));
+      entry.url = uris.next();
+      // further uris are kept as fields
+      for uri in uris {
+        entry
+          .fields
+          .push(CustomField::new(String::from("URL"), uri, FieldKind::Url));
+      }
+    }
+    for field in cipher.fields.iter().flatten() {
+      let value = field.value.clone().unwrap_or_default();
+      let kind = match field.kind {
+        0 => FieldKind::Text,
+        1 => FieldKind::Hidden,
+        2 if value.eq("true") || value.eq("false") => FieldKind::Boolean,
+        2 => FieldKind::Text,
+        // linked fields only point to other fields
+        _ => continue,
+      };
+      entry.fields.push(CustomField::new(
+        field.name.clone().unwrap_or_default(),
+        value,
+        kind,
+      ));
+    }
+    // newest first
+    entry.history = cipher
+      .password_history
+      .iter()
+      .flatten()
+      .filter_map(|history| non_empty(history.password.as_deref()))
+      .collect();
+
+    import.entries.push(entry);
+  }
+
+  import
+}
+
+fn card(card: Option<&CipherCard>) -> Card {
+  let card = match card {
+    Some(card) => card,
+    None => return Card::default(),
+  };
+  // MM/YY
+  let expiry = match (
+    non_empty(card.exp_month.as_deref()),
+    non_empty(card.exp_year.as_deref()),
+  ) {
+    (Some(month), Some(year)) => {
+      let skip = year.chars().count().saturating_sub(2);
+      Some(format!(
+        "{:0>2}/{}",
+        month,
+        year.chars().skip(skip).collect::<String>()
+      ))
+    }
+    _ => None,
+  };
+
+  Card::new(
+    non_empty(card.cardholder_name.as_deref()),
+    non_empty(card.number.as_deref()),
+    non_empty(card.brand.as_deref()),
+    expiry,
+    non_empty(card.code.as_deref()),
+    None,
+  )
+}
+
+/// the values without a counterpart are added as fields
+fn identity(identity: Option<&CipherIdentity>, fields: &mut Vec<CustomField>) -> Identity {
+  let identity = match identity {
+    Some(identity) => identity,
+    None => return Identity::default(),
+  };
+
+  let extra = [
+    ("Middle name", &identity.middle_name, FieldKind::Text),
+    ("State", &identity.state, FieldKind::Text),
+    ("Company", &identity.company, FieldKind::Text),
+    ("Username", &identity.username, FieldKind::Text),
+    ("Social security number", &identity.ssn, FieldKind::Hidden),
+    (
+      "Passport number",
+      &identity.passport_number,
+      FieldKind::Hidden,
+    ),
+    (
+      "License number",
+      &identity.license_number,
+      FieldKind::Hidden,
+    ),
+  ];
+  for (label, value, kind) in extra.iter() {
+    if let Some(value) = non_empty(value.as_deref()) {
+      fields.push(CustomField::new(label.to_string(), value, *kind));
+    }
+  }
+
+  let address = [&identity.address1, &identity.address2, &identity.address3]
+    .iter()
+    .filter_map(|line| non_empty(line.as_deref()))
+    .collect::<Vec<String>>();
+  Identity::new(
+    non_empty(identity.title.as_deref()),
+    non_empty(identity.first_name.as_deref()),
+    non_empty(identity.last_name.as_deref()),
+  )
+  .with_contact(
+    None,
+    non_empty(identity.email.as_deref()),
+    non_empty(identity.phone.as_deref()),
+  )
+  .with_address(
+    Some(address.join("\n")).filter(|address| !address.is_empty()),
+    non_empty(identity.city.as_deref()),
+    non_empty(identity.postal_code.as_deref()),
+    non_empty(identity.country.as_deref()),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_plain() {
+    let import = parse(
+      include_str!("../../../tests/fixtures/import/bitwarden.json"),
+      None,
+    )
+    .unwrap();
+
+    assert_eq!(4, import.entries.len());
+    let login = &import.entries[0];
+    assert_eq!(Some(String::from("GitHub")), login.name);
+    assert_eq!(Some(String::from("alice")), login.login);
+    assert_eq!(Some(String::from("hunter2")), login.password);
+    assert_eq!(Some(String::from("https://github.com/login")), login.url);
+    assert_eq!(Some(String::from("JBSWY3DPEHPK3PXP")), login.otp);
+    assert_eq!(vec!["Work", "Dev"], login.folder);
+    assert!(login.favorite);
+    assert_eq!(vec!["hunter1"], login.history);
+    assert_eq!(Some(1_647_270_566_535), login.created);
+    // the second uri and the custom fields
+    assert_eq!(3, login.fields.len());
+    assert_eq!("URL", login.fields[0].label());
+    assert_eq!(None, login.fields[2].searchable_value());
+
+    let item = |index: usize| serde_json::to_value(&import.entries[index].item).unwrap();
+    assert_eq!("the secret note", item(1)["content"]);
+    assert_eq!("05/27", item(2)["expiry"]);
+    assert_eq!("123", item(2)["cvv"]);
+    assert_eq!("identity", item(3)["type"]);
+    assert_eq!("Main Street 1\nApartment 2", item(3)["address"]);
+    assert_eq!(1, import.entries[3].fields.len());
+
+    // unknown type and a broken item
+    assert_eq!(2, import.skipped.len());
+    assert_eq!(Some(String::from("Broken")), import.skipped[1].name);
+  }
+
+  #[test]
+  fn test_password_protected() {
+    let json = include_str!("../../../tests/fixtures/import/bitwarden_encrypted.json");
+
+    assert!(matches!(
+      parse(json, None),
+      Err(ImportError::PasswordRequired)
+    ));
+    assert!(matches!(
+      parse(json, Some("wrong")),
+      Err(ImportError::DecryptError)
+    ));
+    let import = parse(json, Some("export password")).unwrap();
+    assert_eq!(1, import.entries.len());
+    assert_eq!(Some(String::from("hunter2")), import.entries[0].password);
+  }
+
+  #[test]
+  fn test_kdf_memory_overflow() {
+    let json = r#"{
+      "encrypted": true,
+      "passwordProtected": true,
+      "salt": "salt",
+      "kdfType": 1,
+      "kdfIterations": 3,
+      "kdfMemory": 4294967295,
+      "kdfParallelism": 4,
+      "encKeyValidation_DO_NOT_EDIT": "2.a|b|c",
+      "data": "2.a|b|c"
+    }"#;
+    assert!(matches!(
+      parse(json, Some("password")),
+      Err(ImportError::FormatError(_))
+    ));
+  }
+
+  #[test]
+  fn test_account_encrypted() {
+    assert!(matches!(
+      parse(
+        r#"{ "encrypted": true, "encKeyValidation_DO_NOT_EDIT": "2.a|b|c" }"#,
+        None
+      ),
+      Err(ImportError::Unsupported(_))
+    ));
+  }
+}
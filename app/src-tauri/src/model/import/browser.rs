@@ -0,0 +1,144 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::model::import::{host, non_empty, Import, ImportError, ImportedEntry, Result};
+
+/// read the password csv of chrome, edge or firefox
+///
+/// chrome and edge export `name,url,username,password,note`, firefox exports
+/// `url,username,password,httpRealm,formActionOrigin,guid,timeCreated,timeLastUsed,timePasswordChanged`
+pub fn parse(data: &[u8]) -> Result<Import> {
+  let mut reader = csv::Reader::from_reader(data);
+  let headers = reader
+    .headers()?
+    .iter()
+    .map(|header| header.trim().trim_start_matches('\u{feff}').to_lowercase())
+    .collect::<Vec<String>>();
+  let column = |name: &str| headers.iter().position(|header| header.eq(name));
+
+  let (url, username, password) = (column("url"), column("username"), column("password"));
+  if url.is_none() || password.is_none() {
+    return Err(ImportError::FormatError(String::from(
+      "the url and password columns are missing",
+    )));
+  }
+  let name = column("name");
+  let note = column("note").or_else(|| column("notes"));
+  // unix timestamps in milliseconds, only exported by firefox
+  let created = column("timecreated");
+  let changed = column("timepasswordchanged");
+
+  let mut import = Import::default();
+  for (index, record) in reader.records().enumerate() {
+    // the header is the first row
+    let position = index + 2;
+    let record = match record {
+      Ok(record) => record,
+      Err(error) => {
+        import.skip(position, None, error.to_string().as_str());
+        continue;
+      }
+    };
+    let value = |column: Option<usize>| non_empty(column.and_then(|column| record.get(column)));
+
+    let mut entry = ImportedEntry::default();
+    entry.url = value(url);
+    entry.name = value(name).or_else(|| entry.url.as_deref().and_then(host));
+    entry.login = value(username);
+    entry.password = value(password);
+    entry.description = value(note);
+    entry.created = value(created).and_then(|time| time.parse().ok());
+    entry.modified = value(changed).and_then(|time| time.parse().ok());
+
+    if entry.login.is_none() && entry.password.is_none() {
+      import.skip(position, entry.name.clone(), "no username or password");
+      continue;
+    }
+    import.entries.push(entry);
+  }
+
+  Ok(import)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_chrome() {
+    let import = parse(include_bytes!("../../../tests/fixtures/import/chrome.csv")).unwrap();
+
+    assert_eq!(3, import.entries.len());
+    let github = &import.entries[0];
+    assert_eq!(Some(String::from("github.com")), github.name);
+    assert_eq!(Some(String::from("https://github.com/login")), github.url);
+    assert_eq!(Some(String::from("alice")), github.login);
+    assert_eq!(Some(String::from("hunter2")), github.password);
+    assert_eq!(Some(String::from("work account")), github.description);
+    // quoted values may contain commas and line breaks
+    assert_eq!(
+      Some(String::from("pa,ss\"word")),
+      import.entries[1].password
+    );
+    assert_eq!(
+      Some(String::from("first line\nsecond line")),
+      import.entries[1].description
+    );
+    // the name is taken from the url if missing
+    assert_eq!(Some(String::from("example.org")), import.entries[2].name);
+
+    // an empty row and one with a missing column
+    assert_eq!(
+      vec![5, 6],
+      import
+        .skipped
+        .iter()
+        .map(|skipped| skipped.position)
+        .collect::<Vec<usize>>()
+    );
+  }
+
+  #[test]
+  fn test_firefox() {
+    let import = parse(include_bytes!("../../../tests/fixtures/import/firefox.csv")).unwrap();
+
+    assert_eq!(2, import.entries.len());
+    let entry = &import.entries[0];
+    assert_eq!(Some(String::from("accounts.example.com")), entry.name);
+    assert_eq!(Some(String::from("bob@example.com")), entry.login);
+    assert_eq!(Some(String::from("correct horse")), entry.password);
+    assert_eq!(Some(1_600_000_000_000), entry.created);
+    assert_eq!(Some(1_650_000_000_000), entry.modified);
+    assert!(import.skipped.is_empty());
+  }
+
+  #[test]
+  fn test_unknown_csv() {
+    assert!(matches!(
+      parse(b"title,secret\nfoo,bar\n"),
+      Err(ImportError::FormatError(_))
+    ));
+  }
+}
@@ -0,0 +1,604 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::model::import::{
+  keepass::{self, Protected},
+  Import, ImportError, Result,
+};
+use aes::{
+  cipher::{generic_array::GenericArray, BlockEncrypt, KeyInit},
+  Aes256,
+};
+use argon2::{Algorithm, Argon2, Params, Version};
+use cbc::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+use chacha20::{cipher::StreamCipher, ChaCha20};
+use flate2::read::GzDecoder;
+use hmac::{Hmac, Mac};
+use roxmltree::Document;
+use sha2::{Digest, Sha256, Sha512};
+use std::{
+  collections::HashMap,
+  convert::{TryFrom, TryInto},
+  io::Read,
+};
+use zeroize::Zeroizing;
+
+const SIGNATURE: [u8; 8] = [0x03, 0xd9, 0xa2, 0x9a, 0x67, 0xfb, 0x4b, 0xb5];
+
+// uuids of the ciphers and key derivations
+const AES256: [u8; 16] = [
+  0x31, 0xc1, 0xf2, 0xe6, 0xbf, 0x71, 0x43, 0x50, 0xbe, 0x58, 0x05, 0x21, 0x6a, 0xfc, 0x5a, 0xff,
+];
+const CHACHA20: [u8; 16] = [
+  0xd6, 0x03, 0x8a, 0x2b, 0x8b, 0x6f, 0x4c, 0xb5, 0xa5, 0x24, 0x33, 0x9a, 0x31, 0xdb, 0xb5, 0x9a,
+];
+const AES_KDF: [u8; 16] = [
+  0xc9, 0xd9, 0xf3, 0x9a, 0x62, 0x8a, 0x44, 0x60, 0xbf, 0x74, 0x0d, 0x08, 0xc1, 0x8a, 0x4f, 0xea,
+];
+const ARGON2D: [u8; 16] = [
+  0xef, 0x63, 0x6d, 0xdf, 0x8c, 0x29, 0x44, 0x4b, 0x91, 0xf7, 0xa9, 0xa4, 0x03, 0xe3, 0x0a, 0x0c,
+];
+const ARGON2ID: [u8; 16] = [
+  0x9e, 0x29, 0x8b, 0x19, 0x56, 0xdb, 0x47, 0x73, 0xb2, 0x3d, 0xfc, 0x3e, 0xc6, 0xf0, 0xa1, 0xe6,
+];
+
+// id of the chacha20 stream of the protected values
+const INNER_CHACHA20: u32 = 3;
+
+// limits of the key derivation, the parameters come from the untrusted file
+const MAX_AES_ROUNDS: u64 = 100_000_000;
+// 1 GiB
+const MAX_ARGON2_MEMORY: u64 = 1024 * 1024 * 1024;
+const MAX_ARGON2_ITERATIONS: u64 = 1_000;
+const MAX_ARGON2_PARALLELISM: u64 = 64;
+
+/// the outer header, in front of the encrypted payload
+#[derive(Default)]
+struct Header {
+  cipher: Vec<u8>,
+  compressed: bool,
+  seed: Vec<u8>,
+  iv: Vec<u8>,
+  kdf: HashMap<String, Vec<u8>>,
+}
+
+/// reads the little endian fields of the database
+struct Reader<'a> {
+  data: &'a [u8],
+  position: usize,
+}
+
+impl<'a> Reader<'a> {
+  fn new(data: &'a [u8]) -> Self {
+    Self { data, position: 0 }
+  }
+
+  fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+    let end = self
+      .position
+      .checked_add(length)
+      .filter(|end| *end <= self.data.len())
+      .ok_or_else(|| format_error("unexpected end of the database"))?;
+    let bytes = &self.data[self.position..end];
+    self.position = end;
+    Ok(bytes)
+  }
+
+  fn u8(&mut self) -> Result<u8> {
+    Ok(self.take(1)?[0])
+  }
+
+  fn u32(&mut self) -> Result<u32> {
+    Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+  }
+
+  fn i32(&mut self) -> Result<i32> {
+    Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+  }
+
+  // a field of the headers, `[id][length][data]`
+  fn field(&mut self) -> Result<(u8, &'a [u8])> {
+    let id = self.u8()?;
+    let length = self.u32()? as usize;
+    Ok((id, self.take(length)?))
+  }
+
+  fn rest(&self) -> &'a [u8] {
+    &self.data[self.position..]
+  }
+}
+
+/// read a keepass database of version 4, protected by a password, a key file or both
+pub fn parse(data: &[u8], password: Option<&str>, key_file: Option<&[u8]>) -> Result<Import> {
+  let mut reader = Reader::new(data);
+  if reader.take(8).ok() != Some(&SIGNATURE[..]) {
+    return Err(format_error("not a keepass database"));
+  }
+  let _minor = reader.take(2)?;
+  match u16::from_le_bytes(reader.take(2)?.try_into().unwrap()) {
+    4 => {}
+    major => {
+      return Err(ImportError::Unsupported(format!(
+        "KDBX {} is not supported, save the database as KDBX 4 or export it as xml",
+        major
+      )))
+    }
+  }
+
+  let mut header = Header::default();
+  loop {
+    match reader.field()? {
+      (0, _) => break,
+      (2, cipher) => header.cipher = cipher.to_vec(),
+      (3, flags) => header.compressed = flags.first() == Some(&1),
+      (4, seed) => header.seed = seed.to_vec(),
+      (7, iv) => header.iv = iv.to_vec(),
+      (11, kdf) => header.kdf = variant_dictionary(kdf)?,
+      _ => {}
+    }
+  }
+  let header_data = &data[..reader.position];
+  if Sha256::digest(header_data).as_slice() != reader.take(32)? {
+    return Err(format_error("the header is damaged"));
+  }
+  if password.is_none() && key_file.is_none() {
+    return Err(ImportError::PasswordRequired);
+  }
+
+  // the key is the hash of all parts
+  let mut composite = Sha256::new();
+  if let Some(password) = password {
+    composite.update(Sha256::digest(password.as_bytes()));
+  }
+  if let Some(key_file) = key_file {
+    composite.update(key_file_key(key_file)?.as_slice());
+  }
+  let composite = Zeroizing::new(composite.finalize().to_vec());
+  let transformed = transform(&header.kdf, composite.as_slice())?;
+
+  let key = Zeroizing::new(
+    Sha256::new()
+      .chain_update(&header.seed)
+      .chain_update(transformed.as_slice())
+      .finalize()
+      .to_vec(),
+  );
+  let hmac_key = Zeroizing::new(
+    Sha512::new()
+      .chain_update(&header.seed)
+      .chain_update(transformed.as_slice())
+      .chain_update([1])
+      .finalize()
+      .to_vec(),
+  );
+
+  // a wrong key fails here, before anything gets decrypted
+  block_hmac(hmac_key.as_slice(), u64::MAX)
+    .chain_update(header_data)
+    .verify_slice(reader.take(32)?)
+    .map_err(|_| ImportError::DecryptError)?;
+
+  // the payload is split into blocks, each with its own mac
+  let mut encrypted = Vec::new();
+  for index in 0u64.. {
+    let mac = reader.take(32)?;
+    let length = reader.i32()?;
+    let length_bytes = length.to_le_bytes();
+    let block = reader.take(usize::try_from(length).map_err(|_| format_error("invalid block"))?)?;
+    block_hmac(hmac_key.as_slice(), index)
+      .chain_update(index.to_le_bytes())
+      .chain_update(length_bytes)
+      .chain_update(block)
+      .verify_slice(mac)
+      .map_err(|_| format_error("a block is damaged"))?;
+    if block.is_empty() {
+      break;
+    }
+    encrypted.extend_from_slice(block);
+  }
+
+  let mut payload = decrypt(&header, key.as_slice(), encrypted)?;
+  if header.compressed {
+    let mut decompressed = Zeroizing::new(Vec::new());
+    GzDecoder::new(payload.as_slice())
+      .read_to_end(&mut decompressed)
+      .map_err(|_| format_error("invalid compression"))?;
+    payload = decompressed;
+  }
+
+  // the inner header holds the key of the protected values and the attachments
+  let mut reader = Reader::new(payload.as_slice());
+  let mut stream = None;
+  let mut stream_key = Zeroizing::new(Vec::new());
+  let mut protected = Protected::default();
+  loop {
+    match reader.field()? {
+      (0, _) => break,
+      (1, id) => stream = Some(u32::from_le_bytes(id.try_into().unwrap_or_default())),
+      (2, key) => stream_key = Zeroizing::new(key.to_vec()),
+      // the first byte holds the flags
+      (3, binary) => protected
+        .binaries
+        .push(Zeroizing::new(binary.get(1..).unwrap_or_default().to_vec())),
+      _ => {}
+    }
+  }
+  if stream != Some(INNER_CHACHA20) {
+    return Err(ImportError::Unsupported(String::from(
+      "Only databases with ChaCha20 protected values are supported",
+    )));
+  }
+
+  let xml = std::str::from_utf8(reader.rest()).map_err(|_| format_error("not utf-8"))?;
+  let document = Document::parse(xml)?;
+  protected.strings = unprotect(&document, stream_key.as_slice())?;
+  Ok(keepass::convert(&document, &protected))
+}
+
+fn format_error(message: &str) -> ImportError {
+  ImportError::FormatError(message.to_string())
+}
+
+/// parse the key derivation parameters, `[type][name length][name][value length][value]`
+fn variant_dictionary(data: &[u8]) -> Result<HashMap<String, Vec<u8>>> {
+  let mut reader = Reader::new(data);
+  let _version = reader.take(2)?;
+  let mut dictionary = HashMap::new();
+  loop {
+    if reader.u8()? == 0 {
+      return Ok(dictionary);
+    }
+    let length = reader.i32()?;
+    let name = reader.take(usize::try_from(length).map_err(|_| format_error("invalid kdf"))?)?;
+    let length = reader.i32()?;
+    let value = reader.take(usize::try_from(length).map_err(|_| format_error("invalid kdf"))?)?;
+    dictionary.insert(String::from_utf8_lossy(name).to_string(), value.to_vec());
+  }
+}
+
+/// the 32 byte key of a key file
+///
+/// xml key files contain the key, 32 byte files are the key and every other file gets hashed
+fn key_file_key(data: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+  if let Some(key) = std::str::from_utf8(data)
+    .ok()
+    .and_then(|xml| Document::parse(xml).ok())
+    // any other xml file is hashed like every other file
+    .filter(|document| document.root_element().has_tag_name("KeyFile"))
+    .map(|document| xml_key(&document))
+  {
+    return key;
+  }
+  if data.len() == 32 {
+    return Ok(Zeroizing::new(data.to_vec()));
+  }
+  if let Some(key) = std::str::from_utf8(data)
+    .ok()
+    .filter(|key| key.len() == 64)
+    .and_then(hex)
+  {
+    return Ok(key);
+  }
+  Ok(Zeroizing::new(Sha256::digest(data).to_vec()))
+}
+
+fn xml_key(document: &Document) -> Result<Zeroizing<Vec<u8>>> {
+  let node = |name: &str| document.descendants().find(|node| node.has_tag_name(name));
+  let data = node("Data").ok_or_else(|| format_error("the key file has no key"))?;
+  let text = data.text().unwrap_or_default();
+  match node("Version").and_then(|version| version.text()) {
+    // the hex key with the start of its hash
+    Some(version) if version.starts_with("2.") => {
+      let key = hex(text).ok_or_else(|| format_error("the key file is damaged"))?;
+      let hash = data.attribute("Hash").and_then(hex);
+      match hash {
+        Some(hash) if !Sha256::digest(key.as_slice()).starts_with(hash.as_slice()) => {
+          Err(format_error("the key file is damaged"))
+        }
+        _ => Ok(key),
+      }
+    }
+    _ => base64::decode(text.trim())
+      .map(Zeroizing::new)
+      .map_err(|_| format_error("the key file is damaged")),
+  }
+}
+
+/// decode hex, ignoring whitespace
+fn hex(value: &str) -> Option<Zeroizing<Vec<u8>>> {
+  let digits = value
+    .chars()
+    .filter(|char| !char.is_whitespace())
+    .map(|char| char.to_digit(16).map(|digit| digit as u8))
+    .collect::<Option<Vec<u8>>>()?;
+  if digits.is_empty() || digits.len() % 2 != 0 {
+    return None;
+  }
+  Some(Zeroizing::new(
+    digits
+      .chunks(2)
+      .map(|pair| pair[0] << 4 | pair[1])
+      .collect(),
+  ))
+}
+
+/// derive the key with the key derivation of the header
+fn transform(kdf: &HashMap<String, Vec<u8>>, composite: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+  let bytes = |name: &str| {
+    kdf
+      .get(name)
+      .ok_or_else(|| format_error(format!("{} of the kdf is missing", name).as_str()))
+  };
+  let number = |name: &str| -> Result<u64> {
+    let value = bytes(name)?;
+    match value.len() {
+      4 => Ok(u32::from_le_bytes(value.as_slice().try_into().unwrap()) as u64),
+      8 => Ok(u64::from_le_bytes(value.as_slice().try_into().unwrap())),
+      _ => Err(format_error(
+        format!("{} of the kdf is invalid", name).as_str(),
+      )),
+    }
+  };
+
+  let mut key = Zeroizing::new(vec![0u8; 32]);
+  let uuid = bytes("$UUID")?.as_slice();
+  if uuid == AES_KDF {
+    // the key gets encrypted with the seed for every round
+    let cipher = Aes256::new_from_slice(bytes("S")?).map_err(|_| format_error("invalid seed"))?;
+    let rounds = number("R")?;
+    if rounds > MAX_AES_ROUNDS {
+      return Err(format_error("too many rounds of the kdf"));
+    }
+    key.copy_from_slice(composite);
+    for _ in 0..rounds {
+      for block in key.chunks_mut(16) {
+        cipher.encrypt_block(GenericArray::from_mut_slice(block));
+      }
+    }
+    let hash = Sha256::digest(key.as_slice());
+    key.copy_from_slice(hash.as_slice());
+  } else if uuid == ARGON2D || uuid == ARGON2ID {
+    let algorithm = match uuid == ARGON2D {
+      true => Algorithm::Argon2d,
+      false => Algorithm::Argon2id,
+    };
+    let version = match number("V")? {
+      0x10 => Version::V0x10,
+      _ => Version::V0x13,
+    };
+    let parameter = |name: &str, max: u64| {
+      let value = number(name)?;
+      if value > max {
+        return Err(format_error(
+          format!("{} of the kdf is too large", name).as_str(),
+        ));
+      }
+      u32::try_from(value).map_err(|_| format_error("invalid kdf"))
+    };
+    // the memory is stored in bytes
+    let params = Params::new(
+      parameter("M", MAX_ARGON2_MEMORY)? / 1024,
+      parameter("I", MAX_ARGON2_ITERATIONS)?,
+      parameter("P", MAX_ARGON2_PARALLELISM)?,
+      Some(32),
+    )
+    .map_err(|error| format_error(error.to_string().as_str()))?;
+    Argon2::new(algorithm, version, params)
+      .hash_password_into(composite, bytes("S")?, key.as_mut_slice())
+      .map_err(|error| format_error(error.to_string().as_str()))?;
+  } else {
+    return Err(ImportError::Unsupported(String::from(
+      "Unknown key derivation",
+    )));
+  }
+  Ok(key)
+}
+
+/// the mac of a block, keyed by its index
+fn block_hmac(key: &[u8], index: u64) -> Hmac<Sha256> {
+  let key = Zeroizing::new(
+    Sha512::new()
+      .chain_update(index.to_le_bytes())
+      .chain_update(key)
+      .finalize()
+      .to_vec(),
+  );
+  <Hmac<Sha256> as Mac>::new_from_slice(key.as_slice()).unwrap()
+}
+
+/// decrypt the payload with the cipher of the header
+fn decrypt(header: &Header, key: &[u8], mut encrypted: Vec<u8>) -> Result<Zeroizing<Vec<u8>>> {
+  let cipher = header.cipher.as_slice();
+  if cipher == AES256 {
+    cbc::Decryptor::<Aes256>::new_from_slices(key, &header.iv)
+      .map_err(|_| format_error("invalid iv"))?
+      .decrypt_padded_vec_mut::<Pkcs7>(encrypted.as_slice())
+      .map(Zeroizing::new)
+      .map_err(|_| ImportError::DecryptError)
+  } else if cipher == CHACHA20 {
+    ChaCha20::new_from_slices(key, &header.iv)
+      .map_err(|_| format_error("invalid iv"))?
+      .apply_keystream(encrypted.as_mut_slice());
+    Ok(Zeroizing::new(encrypted))
+  } else {
+    Err(ImportError::Unsupported(String::from(
+      "Only AES-256 and ChaCha20 databases are supported",
+    )))
+  }
+}
+
+/// decrypt the protected values, the stream continues through them in document order
+fn unprotect(document: &Document, key: &[u8]) -> Result<HashMap<u32, String>> {
+  let hash = Zeroizing::new(Sha512::digest(key).to_vec());
+  let mut stream = ChaCha20::new_from_slices(&hash[..32], &hash[32..44]).unwrap();
+
+  let mut strings = HashMap::new();
+  for node in document
+    .descendants()
+    .filter(|node| node.has_tag_name("Value") && node.attribute("Protected") == Some("True"))
+  {
+    let mut value = Zeroizing::new(
+      base64::decode(node.text().unwrap_or_default().trim())
+        .map_err(|_| format_error("invalid protected value"))?,
+    );
+    stream.apply_keystream(value.as_mut_slice());
+    let value = String::from_utf8(value.to_vec()).map_err(|_| ImportError::DecryptError)?;
+    strings.insert(node.id().get(), value);
+  }
+  Ok(strings)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const KDBX: &[u8] = include_bytes!("../../../tests/fixtures/import/keepass.kdbx");
+  const KEY_FILE: &[u8] = include_bytes!("../../../tests/fixtures/import/keepass.keyx");
+
+  #[test]
+  fn test_kdbx() {
+    let import = parse(KDBX, Some("kdbx password"), Some(KEY_FILE)).unwrap();
+    assert_eq!(3, import.entries.len());
+    assert!(import.skipped.is_empty());
+
+    let email = &import.entries[0];
+    assert_eq!(Some(String::from("Email")), email.name);
+    assert_eq!(Some(String::from("bob")), email.login);
+    assert_eq!(Some(String::from("s3cret!")), email.password);
+    assert_eq!(Some(String::from("main account")), email.description);
+    assert!(email.folder.is_empty());
+    assert_eq!(vec!["old password"], email.history);
+    assert_eq!(Some(1_647_270_566_000), email.created);
+    // custom strings keep their protection
+    assert_eq!(2, email.fields.len());
+    assert_eq!(None, email.fields[0].searchable_value());
+    assert_eq!(Some("printed"), email.fields[1].searchable_value());
+    assert_eq!(1, email.attachments.len());
+    assert_eq!("recovery.txt", email.attachments[0].name);
+    assert_eq!(b"write this down\n".to_vec(), email.attachments[0].data);
+
+    // the groups become folders, without the recycle bin
+    assert_eq!(vec!["Banking"], import.entries[1].folder);
+    assert_eq!(Some(String::from("m0ney")), import.entries[1].password);
+    assert_eq!(vec!["Banking", "Cards"], import.entries[2].folder);
+    assert_eq!(
+      (0..=255).collect::<Vec<u8>>(),
+      import.entries[2].attachments[0].data
+    );
+  }
+
+  #[test]
+  fn test_aes() {
+    let import = parse(
+      include_bytes!("../../../tests/fixtures/import/keepass_aes.kdbx"),
+      Some("kdbx password"),
+      None,
+    )
+    .unwrap();
+    assert_eq!(3, import.entries.len());
+    assert_eq!(Some(String::from("s3cret!")), import.entries[0].password);
+  }
+
+  #[test]
+  fn test_wrong_key() {
+    assert!(matches!(
+      parse(KDBX, None, None),
+      Err(ImportError::PasswordRequired)
+    ));
+    // the key file is required as well
+    assert!(matches!(
+      parse(KDBX, Some("kdbx password"), None),
+      Err(ImportError::DecryptError)
+    ));
+    assert!(matches!(
+      parse(KDBX, Some("wrong"), Some(KEY_FILE)),
+      Err(ImportError::DecryptError)
+    ));
+    assert!(matches!(
+      parse(b"not a database", None, None),
+      Err(ImportError::FormatError(_))
+    ));
+  }
+
+  #[test]
+  fn test_kdf_limits() {
+    let composite = [0u8; 32];
+    let mut kdf = HashMap::new();
+    kdf.insert(String::from("$UUID"), AES_KDF.to_vec());
+    kdf.insert(String::from("S"), vec![0u8; 32]);
+    kdf.insert(String::from("R"), u64::MAX.to_le_bytes().to_vec());
+    assert!(matches!(
+      transform(&kdf, &composite),
+      Err(ImportError::FormatError(_))
+    ));
+
+    let mut kdf = HashMap::new();
+    kdf.insert(String::from("$UUID"), ARGON2ID.to_vec());
+    kdf.insert(String::from("S"), vec![0u8; 32]);
+    kdf.insert(String::from("V"), 0x13u32.to_le_bytes().to_vec());
+    kdf.insert(String::from("I"), 2u64.to_le_bytes().to_vec());
+    kdf.insert(String::from("P"), 1u32.to_le_bytes().to_vec());
+    // 4 GiB
+    kdf.insert(String::from("M"), (1u64 << 32).to_le_bytes().to_vec());
+    assert!(matches!(
+      transform(&kdf, &composite),
+      Err(ImportError::FormatError(_))
+    ));
+    // within the limits
+    kdf.insert(String::from("M"), (64u64 * 1024).to_le_bytes().to_vec());
+    assert!(transform(&kdf, &composite).is_ok());
+  }
+
+  #[test]
+  fn test_key_file() {
+    let key = [7u8; 32];
+    assert_eq!(key.to_vec(), *key_file_key(&key).unwrap());
+    let hex = "07".repeat(32);
+    assert_eq!(key.to_vec(), *key_file_key(hex.as_bytes()).unwrap());
+    assert_eq!(
+      Sha256::digest(b"any file").to_vec(),
+      *key_file_key(b"any file").unwrap()
+    );
+
+    // version 1 with the base64 key
+    let xml = format!(
+      "<KeyFile><Meta><Version>1.00</Version></Meta><Key><Data>{}</Data></Key></KeyFile>",
+      base64::encode(key)
+    );
+    assert_eq!(key.to_vec(), *key_file_key(xml.as_bytes()).unwrap());
+    // the hash of version 2 is checked
+    let xml = format!(
+      "<KeyFile><Meta><Version>2.0</Version></Meta><Key><Data Hash=\"00000000\">{}</Data></Key></KeyFile>",
+      hex
+    );
+    assert!(key_file_key(xml.as_bytes()).is_err());
+    // other xml files are used as any file
+    let xml = "<?xml version=\"1.0\"?><settings><Data>AAAA</Data></settings>";
+    assert_eq!(
+      Sha256::digest(xml.as_bytes()).to_vec(),
+      *key_file_key(xml.as_bytes()).unwrap()
+    );
+  }
+}
@@ -0,0 +1,278 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::model::{
+  attachment::MAX_ATTACHMENT_SIZE,
+  field::{CustomField, FieldKind},
+  import::{non_empty, timestamp, Import, ImportedAttachment, ImportedEntry, Result},
+  util,
+};
+use roxmltree::{Document, Node};
+use std::{collections::HashMap, convert::TryFrom};
+use zeroize::Zeroizing;
+
+/// the decrypted content of a database, the xml export contains the strings in plaintext
+#[derive(Default)]
+pub struct Protected {
+  // values of the protected strings by the id of their node
+  pub strings: HashMap<u32, String>,
+  // the attachments, referenced by their index
+  pub binaries: Vec<Zeroizing<Vec<u8>>>,
+}
+
+/// read a keepass 2 xml export
+pub fn parse(xml: &str) -> Result<Import> {
+  let document = Document::parse(xml)?;
+  Ok(convert(&document, &Protected::default()))
+}
+
+/// map the entries of a keepass document
+pub fn convert(document: &Document, protected: &Protected) -> Import {
+  let mut import = Import::default();
+  let meta = child(document.root_element(), "Meta");
+  let root = match child(document.root_element(), "Root").and_then(|root| child(root, "Group")) {
+    Some(root) => root,
+    None => return import,
+  };
+  // the recycle bin is not imported
+  let recycle_bin = meta
+    .and_then(|meta| child(meta, "RecycleBinUUID"))
+    .and_then(|uuid| uuid.text())
+    .filter(|uuid| !uuid.chars().all(|char| char == 'A' || char == '='));
+
+  // the top level group is the database itself
+  group(root, &[], recycle_bin, protected, &mut 0, &mut import);
+  import
+}
+
+fn group(
+  node: Node,
+  path: &[String],
+  recycle_bin: Option<&str>,
+  protected: &Protected,
+  position: &mut usize,
+  import: &mut Import,
+) {
+  for node in node.children().filter(Node::is_element) {
+    match node.tag_name().name() {
+      "Group" => {
+        let uuid = child(node, "UUID").and_then(|uuid| uuid.text());
+        if uuid.is_some() && uuid == recycle_bin {
+          continue;
+        }
+        let mut path = path.to_vec();
+        path.push(text(node, "Name").unwrap_or_else(|| String::from("Unnamed")));
+        group(node, &path, recycle_bin, protected, position, import);
+      }
+      "Entry" => {
+        *position += 1;
+        let position = *position;
+        let mut entry = entry(node, protected);
+        if entry.name.is_none()
+          && entry.login.is_none()
+          && entry.password.is_none()
+          && entry.url.is_none()
+        {
+          import.skip(position, None, "empty entry");
+          continue;
+        }
+        entry.folder = path.to_vec();
+        for binary in node.children().filter(|node| node.has_tag_name("Binary")) {
+          let name = text(binary, "Key").unwrap_or_else(|| String::from("attachment"));
+          let data = child(binary, "Value")
+            .and_then(|value| value.attribute("Ref"))
+            .and_then(|index| index.parse::<usize>().ok())
+            .and_then(|index| protected.binaries.get(index));
+          match data {
+            // the entry itself is still imported
+            Some(data) if data.len() > MAX_ATTACHMENT_SIZE => import.skip(
+              position,
+              entry.name.clone(),
+              format!("attachment {} is larger than 10 MiB", name).as_str(),
+            ),
+            Some(data) => entry.attachments.push(ImportedAttachment {
+              name,
+              data: data.to_vec(),
+            }),
+            None => {}
+          }
+        }
+        import.entries.push(entry);
+      }
+      _ => {}
+    }
+  }
+}
+
+fn entry(node: Node, protected: &Protected) -> ImportedEntry {
+  let mut entry = ImportedEntry::default();
+  for string in node.children().filter(|node| node.has_tag_name("String")) {
+    let key = text(string, "Key").unwrap_or_default();
+    let value = match child(string, "Value") {
+      Some(value) => value,
+      None => continue,
+    };
+    let hidden = value.attribute("Protected") == Some("True")
+      || value.attribute("ProtectInMemory") == Some("True");
+    let value = protected
+      .strings
+      .get(&value.id().get())
+      .map(String::as_str)
+      .or_else(|| value.text());
+
+    match key.as_str() {
+      "Title" => entry.name = non_empty(value),
+      "UserName" => entry.login = non_empty(value),
+      "Password" => entry.password = non_empty(value),
+      "URL" => entry.url = non_empty(value),
+      "Notes" => entry.description = non_empty(value),
+      // otpauth uri of keepassxc or the base32 secret of the totp plugins
+      "otp" | "TOTP Seed" => entry.otp = non_empty(value),
+      _ => {
+        if let Some(value) = non_empty(value) {
+          let kind = match hidden {
+            true => FieldKind::Hidden,
+            false => FieldKind::Text,
+          };
+          entry.fields.push(CustomField::new(key, value, kind));
+        }
+      }
+    }
+  }
+
+  entry.tags = text(node, "Tags")
+    .map(|tags| {
+      tags
+        .split(&[';', ','][..])
+        .filter_map(|tag| non_empty(Some(tag)))
+        .collect()
+    })
+    .unwrap_or_default();
+  if let Some(times) = child(node, "Times") {
+    entry.created = text(times, "CreationTime").and_then(|time| self::time(time.as_str()));
+    entry.modified = text(times, "LastModificationTime").and_then(|time| self::time(time.as_str()));
+  }
+
+  // the history is saved oldest first
+  if let Some(history) = child(node, "History") {
+    let mut passwords = history
+      .children()
+      .filter(|node| node.has_tag_name("Entry"))
+      .filter_map(|old| self::entry(old, protected).password.clone())
+      .collect::<Vec<String>>();
+    passwords.dedup();
+    passwords.retain(|password| Some(password) != entry.password.as_ref());
+    passwords.reverse();
+    entry.history = passwords;
+  }
+
+  entry
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+  node.children().find(|node| node.has_tag_name(name))
+}
+
+fn text(node: Node, name: &str) -> Option<String> {
+  child(node, name)
+    .and_then(|node| node.text())
+    .map(str::to_string)
+}
+
+/// the xml export uses iso timestamps, kdbx 4 the base64 encoded seconds since 0001-01-01
+fn time(value: &str) -> Option<u64> {
+  if let Some(time) = timestamp(value) {
+    return Some(time);
+  }
+
+  let bytes = base64::decode(value.trim()).ok()?;
+  let mut seconds = [0u8; 8];
+  seconds.copy_from_slice(bytes.get(..8)?);
+  // the seconds count from 0001-01-01
+  let offset = util::days_from_civil(1, 1, 1)?.checked_mul(util::DAY as i64)?;
+  let millis = i64::from_le_bytes(seconds)
+    .checked_mul(1000)?
+    .checked_add(offset)?;
+  u64::try_from(millis).ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::model::item::Item;
+
+  #[test]
+  fn test_xml() {
+    let import = parse(include_str!("../../../tests/fixtures/import/keepass.xml")).unwrap();
+
+    assert_eq!(3, import.entries.len());
+    let github = import
+      .entries
+      .iter()
+      .find(|entry| entry.name == Some(String::from("GitHub")))
+      .unwrap();
+    assert_eq!(Some(String::from("alice")), github.login);
+    assert_eq!(Some(String::from("hunter2")), github.password);
+    assert_eq!(Some(String::from("https://github.com")), github.url);
+    assert_eq!(vec!["Internet"], github.folder);
+    assert_eq!(vec!["work", "dev"], github.tags);
+    assert_eq!(Some(1_647_270_566_000), github.created);
+    assert!(github.otp.as_ref().unwrap().starts_with("otpauth://totp/"));
+    // newest first, without the current password
+    assert_eq!(vec!["hunter1", "hunter0"], github.history);
+    assert_eq!(1, github.fields.len());
+    assert_eq!(None, github.fields[0].searchable_value());
+    assert!(matches!(github.item, Item::Login));
+
+    let nested = import
+      .entries
+      .iter()
+      .find(|entry| entry.name == Some(String::from("Router")))
+      .unwrap();
+    assert_eq!(vec!["Internet", "Home"], nested.folder);
+
+    // the recycle bin is ignored and the empty entry skipped
+    assert!(import
+      .entries
+      .iter()
+      .all(|entry| entry.name != Some(String::from("Deleted"))));
+    assert_eq!(1, import.skipped.len());
+  }
+
+  #[test]
+  fn test_time() {
+    assert_eq!(Some(1_647_270_566_000), time("2022-03-14T15:09:26Z"));
+    // kdbx 4
+    // 62_135_596_800 seconds between 0001-01-01 and the unix epoch
+    let seconds = (1_647_270_566_i64 + 62_135_596_800).to_le_bytes();
+    assert_eq!(
+      Some(1_647_270_566_000),
+      time(base64::encode(seconds).as_str())
+    );
+    // before the unix epoch or out of range
+    assert_eq!(None, time(base64::encode(0_i64.to_le_bytes()).as_str()));
+    assert_eq!(None, time(base64::encode(i64::MAX.to_le_bytes()).as_str()));
+  }
+}
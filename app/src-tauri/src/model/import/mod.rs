@@ -0,0 +1,277 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+mod bitwarden;
+mod browser;
+mod kdbx;
+mod keepass;
+mod onepassword;
+
+use crate::model::{field::CustomField, item::Item, util};
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fmt};
+use thiserror::Error;
+use zeroize::Zeroize;
+
+#[derive(Error, Debug)]
+pub enum ImportError {
+  #[error(transparent)]
+  IOError(#[from] std::io::Error),
+
+  #[error(transparent)]
+  CsvError(#[from] csv::Error),
+
+  #[error(transparent)]
+  ParseError(#[from] serde_json::Error),
+
+  #[error(transparent)]
+  XmlError(#[from] roxmltree::Error),
+
+  #[error(transparent)]
+  ZipError(#[from] zip::result::ZipError),
+
+  #[error("Invalid export: {0}")]
+  FormatError(String),
+
+  #[error("Export is protected by a password")]
+  PasswordRequired,
+
+  #[error("Wrong password or damaged export")]
+  DecryptError,
+
+  #[error("{0}")]
+  Unsupported(String),
+}
+
+type Result<T> = std::result::Result<T, ImportError>;
+
+/// the supported exports
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportFormat {
+  // the csv of chrome, edge and firefox
+  BrowserCsv,
+  // plain or password protected json
+  Bitwarden,
+  // keepass 2 xml
+  KeepassXml,
+  // keepass database of version 4
+  Kdbx,
+  OnePassword,
+}
+
+/// an entry read from an export, converted into a password by the user
+#[derive(Default, Clone)]
+pub struct ImportedEntry {
+  pub name: Option<String>,
+  pub login: Option<String>,
+  pub password: Option<String>,
+  pub url: Option<String>,
+  pub description: Option<String>,
+  // names of the folders from the top level down, empty for the top level
+  pub folder: Vec<String>,
+  pub tags: Vec<String>,
+  pub favorite: bool,
+  pub fields: Vec<CustomField>,
+  pub otp: Option<String>,
+  // previous passwords, newest first
+  pub history: Vec<String>,
+  // unix timestamps in milliseconds
+  pub created: Option<u64>,
+  pub modified: Option<u64>,
+  pub item: Item,
+  pub attachments: Vec<ImportedAttachment>,
+}
+
+/// a file attached to an imported entry
+#[derive(Clone)]
+pub struct ImportedAttachment {
+  pub name: String,
+  pub data: Vec<u8>,
+}
+
+impl Zeroize for ImportedAttachment {
+  fn zeroize(&mut self) {
+    self.data.zeroize();
+  }
+}
+
+impl fmt::Debug for ImportedAttachment {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("ImportedAttachment")
+      .field("name", &self.name)
+      .field("size", &self.data.len())
+      .finish()
+  }
+}
+
+impl Zeroize for ImportedEntry {
+  fn zeroize(&mut self) {
+    self.name.zeroize();
+    self.login.zeroize();
+    self.password.zeroize();
+    self.url.zeroize();
+    self.description.zeroize();
+    self.fields.zeroize();
+    self.otp.zeroize();
+    self.history.zeroize();
+    self.item.zeroize();
+    self.attachments.zeroize();
+  }
+}
+
+impl Drop for ImportedEntry {
+  fn drop(&mut self) {
+    self.zeroize();
+  }
+}
+
+impl fmt::Debug for ImportedEntry {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("ImportedEntry")
+      .field("name", &self.name)
+      .field("folder", &self.folder)
+      .field("item", &self.item)
+      .finish()
+  }
+}
+
+/// an entry of the export which could not be imported
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SkippedEntry {
+  // 1 based row or item of the export
+  pub position: usize,
+  pub name: Option<String>,
+  pub reason: String,
+}
+
+/// the parsed content of an export
+#[derive(Default, Debug)]
+pub struct Import {
+  pub entries: Vec<ImportedEntry>,
+  pub skipped: Vec<SkippedEntry>,
+}
+
+impl Import {
+  fn skip(&mut self, position: usize, name: Option<String>, reason: &str) {
+    self.skipped.push(SkippedEntry {
+      position,
+      name,
+      reason: reason.to_string(),
+    });
+  }
+}
+
+/// the outcome of an import
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct ImportReport {
+  pub imported: usize,
+  // entries which already exist in the vault
+  pub duplicates: usize,
+  pub skipped: Vec<SkippedEntry>,
+}
+
+/// read an export, the password and key file are only needed for encrypted exports
+pub fn parse(
+  format: ImportFormat,
+  data: &[u8],
+  password: Option<&str>,
+  key_file: Option<&[u8]>,
+) -> Result<Import> {
+  match format {
+    ImportFormat::BrowserCsv => browser::parse(data),
+    ImportFormat::Bitwarden => bitwarden::parse(text(data)?, password),
+    ImportFormat::KeepassXml => keepass::parse(text(data)?),
+    ImportFormat::Kdbx => kdbx::parse(data, password, key_file),
+    ImportFormat::OnePassword => onepassword::parse(data),
+  }
+}
+
+/// the data as utf-8, without a byte order mark
+fn text(data: &[u8]) -> Result<&str> {
+  let text =
+    std::str::from_utf8(data).map_err(|_| ImportError::FormatError(String::from("not utf-8")))?;
+  Ok(text.trim_start_matches('\u{feff}'))
+}
+
+/// none for empty or blank values
+fn non_empty(value: Option<&str>) -> Option<String> {
+  value
+    .map(str::trim)
+    .filter(|value| !value.is_empty())
+    .map(str::to_string)
+}
+
+/// the host of an url without `www.`, used as name and to find duplicates
+pub fn host(url: &str) -> Option<String> {
+  let url = url::Url::parse(url.trim()).ok()?;
+  let host = url.host_str()?.to_lowercase();
+  Some(host.trim_start_matches("www.").to_string())
+}
+
+/// parse an utc timestamp like `2022-03-14T15:09:26.535Z` into unix milliseconds
+fn timestamp(value: &str) -> Option<u64> {
+  let value = value.trim().trim_end_matches('Z');
+  let (date, time) = value.split_once('T').unwrap_or((value, "00:00:00"));
+
+  let mut date = date.splitn(3, '-').map(|part| part.parse::<u32>().ok());
+  let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+  let (time, fraction) = time.split_once('.').unwrap_or((time, "0"));
+  let mut time = time.splitn(3, ':').map(|part| part.parse::<i64>().ok());
+  let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+  if !(0..24).contains(&hour) || !(0..60).contains(&minute) || !(0..=60).contains(&second) {
+    return None;
+  }
+  // only the milliseconds are kept
+  let millis = format!("{:0<3}", fraction.get(..3).unwrap_or(fraction))
+    .parse::<u16>()
+    .ok()?;
+
+  let days = util::days_from_civil(i64::from(year), month, day)?;
+  let millis = ((hour * 60 + minute) * 60 + second) * 1000 + i64::from(millis);
+  let millis = days.checked_mul(util::DAY as i64)?.checked_add(millis)?;
+  u64::try_from(millis).ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_timestamp() {
+    assert_eq!(Some(0), timestamp("1970-01-01T00:00:00Z"));
+    assert_eq!(
+      Some(1_647_270_566_535),
+      timestamp("2022-03-14T15:09:26.535Z")
+    );
+    assert_eq!(Some(951_782_400_000), timestamp("2000-02-29T00:00:00Z"));
+    assert_eq!(Some(1_647_216_000_000), timestamp("2022-03-14"));
+    assert_eq!(None, timestamp("2022-13-01T00:00:00Z"));
+    assert_eq!(None, timestamp("yesterday"));
+    assert_eq!(None, timestamp("2022-02-30T00:00:00Z"));
+    assert_eq!(None, timestamp("1969-12-31T23:59:59Z"));
+    assert_eq!(None, timestamp("4294967295-12-31T00:00:00Z"));
+  }
+}
@@ -0,0 +1,373 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::model::{
+  field::{CustomField, FieldKind},
+  import::{non_empty, Import, ImportedEntry, Result},
+  item::{Card, Identity, Item, Note},
+  util,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{
+  collections::HashMap,
+  io::{Cursor, Read},
+};
+use zeroize::Zeroizing;
+
+#[derive(Deserialize)]
+struct Export {
+  accounts: Vec<Account>,
+}
+
+#[derive(Deserialize)]
+struct Account {
+  vaults: Vec<Vault>,
+}
+
+#[derive(Deserialize)]
+struct Vault {
+  attrs: VaultAttributes,
+  // parsed one by one, so a single broken item does not fail the import
+  items: Vec<Value>,
+}
+
+#[derive(Deserialize)]
+struct VaultAttributes {
+  name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OnePasswordItem {
+  #[serde(default)]
+  fav_index: u64,
+  // unix timestamps in seconds
+  created_at: Option<u64>,
+  updated_at: Option<u64>,
+  #[serde(default)]
+  state: String,
+  category_uuid: String,
+  details: Details,
+  overview: Overview,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Details {
+  #[serde(default)]
+  login_fields: Vec<LoginField>,
+  notes_plain: Option<String>,
+  #[serde(default)]
+  sections: Vec<Section>,
+  #[serde(default)]
+  password_history: Vec<History>,
+  // the password of the password category
+  password: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LoginField {
+  value: Option<String>,
+  designation: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Section {
+  #[serde(default)]
+  fields: Vec<SectionField>,
+}
+
+#[derive(Deserialize)]
+struct SectionField {
+  #[serde(default)]
+  title: String,
+  #[serde(default)]
+  id: String,
+  // an object with the type as single key
+  value: Value,
+}
+
+#[derive(Deserialize)]
+struct History {
+  value: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Overview {
+  title: Option<String>,
+  url: Option<String>,
+  #[serde(default)]
+  tags: Vec<String>,
+}
+
+/// the categories of 1password
+const CREDIT_CARD: &str = "002";
+const SECURE_NOTE: &str = "003";
+const IDENTITY: &str = "004";
+const PASSWORD: &str = "005";
+
+/// read a 1pux export of 1password, the zip contains the items in `export.data`
+///
+/// the vaults become folders, the files of the export are not imported
+pub fn parse(data: &[u8]) -> Result<Import> {
+  let mut archive = zip::ZipArchive::new(Cursor::new(data))?;
+  let mut content = Zeroizing::new(String::new());
+  archive
+    .by_name("export.data")?
+    .read_to_string(&mut content)?;
+  let export = serde_json::from_str::<Export>(content.as_str())?;
+
+  let mut import = Import::default();
+  let vaults = export
+    .accounts
+    .into_iter()
+    .flat_map(|account| account.vaults);
+  for vault in vaults {
+    for item in vault.items {
+      let position = import.entries.len() + import.skipped.len() + 1;
+      let name = item["overview"]["title"].as_str().map(str::to_string);
+      let item = match serde_json::from_value::<OnePasswordItem>(item) {
+        Ok(item) => item,
+        Err(error) => {
+          import.skip(position, name, error.to_string().as_str());
+          continue;
+        }
+      };
+      if item.state.eq("archived") {
+        import.skip(position, name, "archived item");
+        continue;
+      }
+
+      let mut entry = convert(&item);
+      entry.folder = vec![vault.attrs.name.clone()];
+      import.entries.push(entry);
+    }
+  }
+
+  Ok(import)
+}
+
+fn convert(item: &OnePasswordItem) -> ImportedEntry {
+  let mut entry = ImportedEntry::default();
+  entry.name = non_empty(item.overview.title.as_deref());
+  entry.url = non_empty(item.overview.url.as_deref());
+  entry.tags = item.overview.tags.clone();
+  entry.favorite = item.fav_index > 0;
+  entry.created = item
+    .created_at
+    .and_then(|seconds| seconds.checked_mul(1000));
+  entry.modified = item
+    .updated_at
+    .and_then(|seconds| seconds.checked_mul(1000));
+  entry.history = item
+    .details
+    .password_history
+    .iter()
+    .filter_map(|history| non_empty(history.value.as_deref()))
+    .collect();
+
+  let notes = non_empty(item.details.notes_plain.as_deref());
+  match item.category_uuid.as_str() {
+    SECURE_NOTE => entry.item = Item::Note(Note::new(notes)),
+    _ => entry.description = notes,
+  }
+  for field in item.details.login_fields.iter() {
+    match field.designation.as_deref() {
+      Some("username") => entry.login = non_empty(field.value.as_deref()),
+      Some("password") => entry.password = non_empty(field.value.as_deref()),
+      _ => {}
+    }
+  }
+  if item.category_uuid.eq(PASSWORD) {
+    entry.password = non_empty(item.details.password.as_deref());
+  }
+
+  // the values of the card and identity items by the name of their field
+  let mut values = HashMap::new();
+  for field in item
+    .details
+    .sections
+    .iter()
+    .flat_map(|section| &section.fields)
+  {
+    let (kind, value) = match value(&field.value) {
+      Some(value) => value,
+      None => continue,
+    };
+    let target = match (item.category_uuid.as_str(), field.id.as_str()) {
+      (CREDIT_CARD, "cardholder") => "holder",
+      (CREDIT_CARD, "ccnum") => "number",
+      (CREDIT_CARD, "type") => "brand",
+      (CREDIT_CARD, "expiry") => "expiry",
+      (CREDIT_CARD, "cvv") => "cvv",
+      (CREDIT_CARD, "pin") => "pin",
+      (IDENTITY, "firstname") => "first_name",
+      (IDENTITY, "lastname") => "last_name",
+      (IDENTITY, "birthdate") => "birthday",
+      (IDENTITY, "email") => "email",
+      (IDENTITY, "defphone") => "phone",
+      // the first one time password, the others are kept as fields
+      _ if kind == Kind::Otp && entry.otp.is_none() => {
+        entry.otp = Some(value);
+        continue;
+      }
+      _ => {
+        let kind = match kind {
+          Kind::Concealed | Kind::Otp => FieldKind::Hidden,
+          Kind::Url => FieldKind::Url,
+          Kind::Date => FieldKind::Date,
+          Kind::Text => FieldKind::Text,
+        };
+        entry
+          .fields
+          .push(CustomField::new(field.title.clone(), value, kind));
+        continue;
+      }
+    };
+    values.insert(target, value);
+  }
+
+  let mut take = |name: &str| values.remove(name);
+  match item.category_uuid.as_str() {
+    CREDIT_CARD => {
+      entry.item = Item::Card(Card::new(
+        take("holder"),
+        take("number"),
+        take("brand"),
+        take("expiry"),
+        take("cvv"),
+        take("pin"),
+      ))
+    }
+    IDENTITY => {
+      entry.item = Item::Identity(
+        Identity::new(None, take("first_name"), take("last_name")).with_contact(
+          take("birthday"),
+          take("email"),
+          take("phone"),
+        ),
+      )
+    }
+    // logins and everything else keep the data as fields of a login
+    _ => {}
+  }
+  entry
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum Kind {
+  Text,
+  Concealed,
+  Url,
+  Date,
+  Otp,
+}
+
+/// the value of a section field and how it should be shown
+fn value(value: &Value) -> Option<(Kind, String)> {
+  let (kind, value) = value.as_object()?.iter().next()?;
+  let value = match (kind.as_str(), value) {
+    ("concealed", Value::String(value)) | ("creditCardNumber", Value::String(value)) => {
+      (Kind::Concealed, value.clone())
+    }
+    ("totp", Value::String(value)) => (Kind::Otp, value.clone()),
+    ("url", Value::String(value)) => (Kind::Url, value.clone()),
+    // unix timestamp in seconds
+    ("date", Value::Number(seconds)) => {
+      let days = seconds.as_i64()?.div_euclid(24 * 60 * 60);
+      (Kind::Date, date(days)?)
+    }
+    // YYYYMM
+    ("monthYear", Value::Number(month)) => {
+      let month = month.as_u64()?;
+      (
+        Kind::Text,
+        format!("{:02}/{:02}", month % 100, month / 100 % 100),
+      )
+    }
+    ("email", Value::Object(email)) => (
+      Kind::Text,
+      email.get("email_address")?.as_str()?.to_string(),
+    ),
+    (_, Value::String(value)) => (Kind::Text, value.clone()),
+    _ => return None,
+  };
+
+  match value.1.trim().is_empty() {
+    true => None,
+    false => Some(value),
+  }
+}
+
+/// YYYY-MM-DD of the days since the unix epoch
+fn date(days: i64) -> Option<String> {
+  let (year, month, day) = util::civil_from_days(days)?;
+  Some(format!("{:04}-{:02}-{:02}", year, month, day))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_1pux() {
+    let import = parse(include_bytes!("../../../tests/fixtures/import/export.1pux")).unwrap();
+
+    assert_eq!(4, import.entries.len());
+    let login = &import.entries[0];
+    assert_eq!(Some(String::from("GitHub")), login.name);
+    assert_eq!(Some(String::from("alice")), login.login);
+    assert_eq!(Some(String::from("hunter2")), login.password);
+    assert_eq!(Some(String::from("https://github.com")), login.url);
+    assert_eq!(Some(String::from("work account")), login.description);
+    assert_eq!(vec!["Personal"], login.folder);
+    assert_eq!(vec!["work"], login.tags);
+    assert!(login.favorite);
+    assert_eq!(Some(1_647_270_566_000), login.created);
+    assert!(login.otp.as_ref().unwrap().starts_with("otpauth://"));
+    assert_eq!(vec!["hunter1"], login.history);
+    assert_eq!(2, login.fields.len());
+    assert_eq!(Some("1990-05-17"), login.fields[1].searchable_value());
+
+    let item = |index: usize| serde_json::to_value(&import.entries[index].item).unwrap();
+    assert_eq!("the secret note", item(1)["content"]);
+    assert_eq!("4111111111111111", item(2)["number"]);
+    assert_eq!("05/27", item(2)["expiry"]);
+    assert_eq!(vec!["Shared"], import.entries[3].folder);
+    assert_eq!(Some(String::from("s3cret")), import.entries[3].password);
+
+    // archived and broken items
+    assert_eq!(2, import.skipped.len());
+  }
+
+  #[test]
+  fn test_date() {
+    assert_eq!(Some(String::from("1970-01-01")), date(0));
+    assert_eq!(Some(String::from("2000-02-29")), date(11_016));
+    assert_eq!(Some(String::from("1969-12-31")), date(-1));
+    assert_eq!(None, date(i64::MAX));
+  }
+}
@@ -0,0 +1,184 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::model::{
+  encryption::{CipherText, Encryption, EncryptionError},
+  kdf::Kdf,
+  lock::VaultLock,
+};
+use pbkdf2::password_hash::{self, rand_core::OsRng, SaltString};
+use serde::{Deserialize, Serialize};
+use std::{
+  fs,
+  io::{self, ErrorKind, Write},
+  path::PathBuf,
+};
+use tempfile::NamedTempFile;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum IndexError {
+  #[error(transparent)]
+  IOError(#[from] io::Error),
+
+  #[error(transparent)]
+  ParseError(#[from] serde_json::Error),
+
+  #[error(transparent)]
+  EncryptionError(#[from] EncryptionError),
+
+  #[error(transparent)]
+  HashError(#[from] password_hash::Error),
+
+  #[error("Index is updated by another session")]
+  LockedError,
+}
+
+type Result<T> = std::result::Result<T, IndexError>;
+
+/// encrypted mapping of the usernames to the files of the private vaults
+///
+/// every entry is encrypted with a key derived from the username and the stored salt only. the
+/// index hides the usernames from a look at the directory, but anyone with a copy can test guessed
+/// usernames offline at the cost of the kdf, and it always reveals the number of private vaults
+#[derive(Deserialize, Serialize)]
+pub struct VaultIndex {
+  salt: String,
+  kdf: Kdf,
+  // the encrypted file ids
+  entries: Vec<CipherText>,
+}
+
+impl VaultIndex {
+  /// the index lives in `private/index.json`
+  fn directory(directory: &PathBuf) -> PathBuf {
+    directory.join("private")
+  }
+
+  fn load(directory: &PathBuf) -> Result<Self> {
+    match fs::read_to_string(directory.join("index.json")) {
+      Ok(content) => Ok(serde_json::from_str(content.as_str())?),
+      Err(error) if error.kind() == ErrorKind::NotFound => Ok(Self {
+        salt: SaltString::generate(&mut OsRng).as_str().to_string(),
+        kdf: Kdf::default(),
+        entries: Vec::new(),
+      }),
+      Err(error) => Err(error.into()),
+    }
+  }
+
+  fn save(&self, directory: &PathBuf) -> Result<()> {
+    let mut file = NamedTempFile::new_in(directory)?;
+    file.write_all(serde_json::to_string(self)?.as_bytes())?;
+    file.as_file().sync_all()?;
+    file
+      .persist(directory.join("index.json"))
+      .map_err(|error| error.error)?;
+    Ok(())
+  }
+
+  /// the key of the entries of a user
+  fn key(&self, username: &str) -> Result<Encryption> {
+    let salt = SaltString::new(self.salt.as_str())?;
+    let hash = self.kdf.hash(username.to_lowercase().as_bytes(), &salt)?;
+    Ok(Encryption::new(hash.key.as_ref()))
+  }
+
+  /// get the file of a private vault
+  pub fn find(directory: &PathBuf, username: &str) -> Result<Option<String>> {
+    let index = Self::load(&Self::directory(directory))?;
+    // skip the key derivation without any private vault
+    if index.entries.is_empty() {
+      return Ok(None);
+    }
+
+    let key = index.key(username)?;
+    Ok(index.entries.iter().find_map(|entry| {
+      key
+        .decrypt(entry.ciphertext.clone(), entry.nonce.clone())
+        .ok()
+    }))
+  }
+
+  /// set or remove the file of a user
+  pub fn update(directory: &PathBuf, username: &str, file: Option<&str>) -> Result<()> {
+    let directory = Self::directory(directory);
+    fs::create_dir_all(&directory)?;
+    // other sessions could update the index at the same time
+    let _lock = VaultLock::acquire(&directory, "index")?.ok_or(IndexError::LockedError)?;
+
+    let mut index = Self::load(&directory)?;
+    let key = index.key(username)?;
+    index.entries.retain(|entry| {
+      key
+        .decrypt(entry.ciphertext.clone(), entry.nonce.clone())
+        .is_err()
+    });
+    if let Some(file) = file {
+      index.entries.push(key.encrypt(file)?);
+    }
+    index.save(&directory)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  #[test]
+  fn test_index() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+
+    assert_eq!(None, VaultIndex::find(&path, "alice").unwrap());
+    VaultIndex::update(&path, "Alice", Some("first")).unwrap();
+    VaultIndex::update(&path, "bob", Some("second")).unwrap();
+    assert_eq!(
+      Some(String::from("first")),
+      VaultIndex::find(&path, "alice").unwrap()
+    );
+    assert_eq!(None, VaultIndex::find(&path, "carol").unwrap());
+
+    // neither the usernames nor the files are readable
+    let content = fs::read_to_string(path.join("private").join("index.json")).unwrap();
+    for plain in ["alice", "bob", "first", "second"] {
+      assert!(!content.contains(plain));
+    }
+
+    // replace and remove
+    VaultIndex::update(&path, "alice", Some("third")).unwrap();
+    assert_eq!(
+      Some(String::from("third")),
+      VaultIndex::find(&path, "alice").unwrap()
+    );
+    VaultIndex::update(&path, "alice", None).unwrap();
+    assert_eq!(None, VaultIndex::find(&path, "alice").unwrap());
+    assert_eq!(
+      Some(String::from("second")),
+      VaultIndex::find(&path, "bob").unwrap()
+    );
+  }
+}
@@ -0,0 +1,293 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use zeroize::Zeroize;
+
+// wipe every field of the secret structs
+macro_rules! zeroize_fields {
+  ($type:ty, $($field:ident),*) => {
+    impl Zeroize for $type {
+      fn zeroize(&mut self) {
+        $(self.$field.zeroize();)*
+      }
+    }
+  };
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct Note {
+  content: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct Card {
+  holder: Option<String>,
+  number: Option<String>,
+  brand: Option<String>,
+  // MM/YY
+  expiry: Option<String>,
+  cvv: Option<String>,
+  pin: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct Identity {
+  title: Option<String>,
+  first_name: Option<String>,
+  last_name: Option<String>,
+  birthday: Option<String>,
+  email: Option<String>,
+  phone: Option<String>,
+  address: Option<String>,
+  city: Option<String>,
+  postal_code: Option<String>,
+  country: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct ApiKey {
+  key: Option<String>,
+  secret: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct SshKey {
+  private_key: Option<String>,
+  public_key: Option<String>,
+  passphrase: Option<String>,
+}
+
+impl Note {
+  pub fn new(content: Option<String>) -> Self {
+    Self { content }
+  }
+}
+
+impl Card {
+  pub fn new(
+    holder: Option<String>,
+    number: Option<String>,
+    brand: Option<String>,
+    expiry: Option<String>,
+    cvv: Option<String>,
+    pin: Option<String>,
+  ) -> Self {
+    Self {
+      holder,
+      number,
+      brand,
+      expiry,
+      cvv,
+      pin,
+    }
+  }
+}
+
+impl Identity {
+  /// create a new identity, the other values are added with the builder methods
+  pub fn new(title: Option<String>, first_name: Option<String>, last_name: Option<String>) -> Self {
+    Self {
+      title,
+      first_name,
+      last_name,
+      ..Self::default()
+    }
+  }
+
+  pub fn with_contact(
+    mut self,
+    birthday: Option<String>,
+    email: Option<String>,
+    phone: Option<String>,
+  ) -> Self {
+    self.birthday = birthday;
+    self.email = email;
+    self.phone = phone;
+    self
+  }
+
+  pub fn with_address(
+    mut self,
+    address: Option<String>,
+    city: Option<String>,
+    postal_code: Option<String>,
+    country: Option<String>,
+  ) -> Self {
+    self.address = address;
+    self.city = city;
+    self.postal_code = postal_code;
+    self.country = country;
+    self
+  }
+}
+
+zeroize_fields!(Note, content);
+zeroize_fields!(Card, holder, number, brand, expiry, cvv, pin);
+zeroize_fields!(
+  Identity,
+  title,
+  first_name,
+  last_name,
+  birthday,
+  email,
+  phone,
+  address,
+  city,
+  postal_code,
+  country
+);
+zeroize_fields!(ApiKey, key, secret);
+zeroize_fields!(SshKey, private_key, public_key, passphrase);
+
+/// the type of a vault item
+///
+/// logins use the login, password and url of the `PasswordData` itself
+#[derive(Deserialize, Serialize, Clone, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Item {
+  // entries saved before the item types were logins
+  #[default]
+  Login,
+  Note(Note),
+  Card(Card),
+  Identity(Identity),
+  ApiKey(ApiKey),
+  SshKey(SshKey),
+}
+
+/// the type of a vault item without any data, used to create new items
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+  #[default]
+  Login,
+  Note,
+  Card,
+  Identity,
+  ApiKey,
+  SshKey,
+}
+
+impl Item {
+  /// create a new empty item
+  pub fn new(kind: ItemKind) -> Self {
+    match kind {
+      ItemKind::Login => Item::Login,
+      ItemKind::Note => Item::Note(Note::default()),
+      ItemKind::Card => Item::Card(Card::default()),
+      ItemKind::Identity => Item::Identity(Identity::default()),
+      ItemKind::ApiKey => Item::ApiKey(ApiKey::default()),
+      ItemKind::SshKey => Item::SshKey(SshKey::default()),
+    }
+  }
+
+  pub fn kind(&self) -> ItemKind {
+    match self {
+      Item::Login => ItemKind::Login,
+      Item::Note(_) => ItemKind::Note,
+      Item::Card(_) => ItemKind::Card,
+      Item::Identity(_) => ItemKind::Identity,
+      Item::ApiKey(_) => ItemKind::ApiKey,
+      Item::SshKey(_) => ItemKind::SshKey,
+    }
+  }
+
+  /// the content of secure notes
+  pub fn notes(&self) -> Option<&String> {
+    match self {
+      Item::Note(note) => note.content.as_ref(),
+      _ => None,
+    }
+  }
+
+  /// the secret chosen by a human, which should be analysed for its strength
+  ///
+  /// logins are handled by `PasswordData`, generated keys and card numbers are never analysed
+  pub fn passphrase(&self) -> Option<&String> {
+    match self {
+      Item::SshKey(key) => key.passphrase.as_ref(),
+      _ => None,
+    }
+  }
+}
+
+impl Zeroize for Item {
+  fn zeroize(&mut self) {
+    match self {
+      Item::Login => {}
+      Item::Note(note) => note.zeroize(),
+      Item::Card(card) => card.zeroize(),
+      Item::Identity(identity) => identity.zeroize(),
+      Item::ApiKey(key) => key.zeroize(),
+      Item::SshKey(key) => key.zeroize(),
+    }
+  }
+}
+
+// the content is always redacted
+impl fmt::Debug for Item {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{:?}", self.kind())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_serialization() {
+    let item = Item::SshKey(SshKey {
+      private_key: Some(String::from("private")),
+      public_key: None,
+      passphrase: Some(String::from("passphrase")),
+    });
+    let raw = serde_json::to_value(&item).unwrap();
+    assert_eq!("ssh_key", raw["type"]);
+    assert_eq!("private", raw["private_key"]);
+
+    let parsed = serde_json::from_value::<Item>(raw).unwrap();
+    assert_eq!(ItemKind::SshKey, parsed.kind());
+    assert_eq!(Some(&String::from("passphrase")), parsed.passphrase());
+    assert_eq!("SshKey", format!("{:?}", parsed));
+  }
+
+  #[test]
+  fn test_new() {
+    let kinds = [
+      ItemKind::Login,
+      ItemKind::Note,
+      ItemKind::Card,
+      ItemKind::Identity,
+      ItemKind::ApiKey,
+      ItemKind::SshKey,
+    ];
+    for kind in kinds.iter() {
+      assert_eq!(*kind, Item::new(*kind).kind());
+    }
+  }
+}
@@ -0,0 +1,193 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use argon2::{Algorithm, Argon2, Params, Version, ARGON2ID_IDENT};
+use hmac::Hmac;
+use pbkdf2::password_hash::{Error, PasswordHash, SaltString};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::convert::TryInto;
+use zeroize::Zeroizing;
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum KdfAlgorithm {
+  Pbkdf2,
+  Argon2id,
+}
+
+/// parameters of the master password hashing, stored next to the salt
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Kdf {
+  algorithm: KdfAlgorithm,
+  // memory cost in KiB, unused by pbkdf2
+  memory: u32,
+  // iterations or rounds
+  iterations: u32,
+  // lanes, unused by pbkdf2
+  parallelism: u32,
+}
+
+/// output of the password hashing, wiped on the drop
+pub struct DerivedKey {
+  // the raw hash, used as key
+  pub key: Zeroizing<[u8; 32]>,
+  // the hash in the phc string format
+  pub encoded: Zeroizing<String>,
+}
+
+impl Default for Kdf {
+  fn default() -> Self {
+    Self::argon2id(19456, 2, 1)
+  }
+}
+
+impl Kdf {
+  /// create new argon2id descriptor
+  pub fn argon2id(memory: u32, iterations: u32, parallelism: u32) -> Self {
+    Self {
+      algorithm: KdfAlgorithm::Argon2id,
+      memory,
+      iterations,
+      parallelism,
+    }
+  }
+
+  /// the parameters used before the kdf became configurable
+  pub fn legacy() -> Self {
+    Self {
+      algorithm: KdfAlgorithm::Pbkdf2,
+      memory: 0,
+      iterations: 10_000,
+      parallelism: 1,
+    }
+  }
+
+  /// check whether the vault should be upgraded on the next login
+  pub fn outdated(&self) -> bool {
+    self.algorithm.ne(&KdfAlgorithm::Argon2id)
+  }
+
+  /// hash the password, the raw hash is 32 bytes long
+  pub fn hash(&self, password: &[u8], salt: &SaltString) -> Result<DerivedKey, Error> {
+    let mut salt_buffer = [0u8; 64];
+    let salt_bytes = salt.b64_decode(&mut salt_buffer)?;
+    let mut key = Zeroizing::new([0u8; 32]);
+
+    // the phc string without the output, so no copy of the hash is left behind
+    let header = match self.algorithm {
+      KdfAlgorithm::Pbkdf2 => {
+        let params = pbkdf2::Params {
+          rounds: self.iterations,
+          output_length: 32,
+        };
+        pbkdf2::pbkdf2::<Hmac<Sha256>>(password, salt_bytes, self.iterations, key.as_mut());
+        PasswordHash {
+          algorithm: pbkdf2::Algorithm::Pbkdf2Sha256.ident(),
+          version: None,
+          params: params.try_into()?,
+          salt: Some(salt.as_salt()),
+          hash: None,
+        }
+      }
+      KdfAlgorithm::Argon2id => {
+        let params = Params::new(self.memory, self.iterations, self.parallelism, Some(32))?;
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params.clone()).hash_password_into(
+          password,
+          salt_bytes,
+          key.as_mut(),
+        )?;
+        PasswordHash {
+          algorithm: ARGON2ID_IDENT,
+          version: Some(Version::V0x13.into()),
+          params: (&params).try_into()?,
+          salt: Some(salt.as_salt()),
+          hash: None,
+        }
+      }
+    }
+    .to_string();
+
+    // append the unpadded base64 of the hash
+    let mut output = Zeroizing::new([0u8; 43]);
+    base64::encode_config_slice(key.as_ref(), base64::STANDARD_NO_PAD, output.as_mut());
+    let mut encoded = Zeroizing::new(String::with_capacity(header.len() + 1 + output.len()));
+    encoded.push_str(header.as_str());
+    encoded.push('$');
+    encoded.extend(output.iter().map(|byte| *byte as char));
+
+    Ok(DerivedKey { key, encoded })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pbkdf2::{password_hash::PasswordHasher, Pbkdf2};
+
+  #[test]
+  fn test_legacy_hash() {
+    // the hash has to equal the one of the vaults before the kdf descriptor
+    let salt = SaltString::new("mgtQBCKsArg2KiDaL1xkbQ").unwrap();
+    let hash = Kdf::legacy().hash("test".as_bytes(), &salt).unwrap();
+    let expected = Pbkdf2.hash_password("test".as_bytes(), &salt).unwrap();
+
+    assert_eq!(hash.encoded.as_str(), expected.to_string());
+    assert_eq!(&hash.key[..], expected.hash.unwrap().as_bytes());
+  }
+
+  #[test]
+  fn test_argon2id_hash() {
+    let salt = SaltString::new("mgtQBCKsArg2KiDaL1xkbQ").unwrap();
+    let hash = Kdf::argon2id(1024, 1, 1)
+      .hash("test".as_bytes(), &salt)
+      .unwrap();
+
+    let expected = Argon2::new(
+      Algorithm::Argon2id,
+      Version::V0x13,
+      Params::new(1024, 1, 1, Some(32)).unwrap(),
+    )
+    .hash_password("test".as_bytes(), &salt)
+    .unwrap();
+
+    assert_eq!(hash.encoded.as_str(), expected.to_string());
+    assert_eq!(&hash.key[..], expected.hash.unwrap().as_bytes());
+  }
+
+  #[test]
+  fn test_invalid_params() {
+    let salt = SaltString::new("mgtQBCKsArg2KiDaL1xkbQ").unwrap();
+    assert!(Kdf::argon2id(0, 1, 1)
+      .hash("test".as_bytes(), &salt)
+      .is_err());
+  }
+
+  #[test]
+  fn test_outdated() {
+    assert!(Kdf::legacy().outdated());
+    assert!(!Kdf::default().outdated());
+  }
+}
@@ -0,0 +1,88 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use fs2::FileExt;
+use std::{
+  fs::{File, OpenOptions},
+  io,
+  path::Path,
+};
+
+/// advisory lock of a vault, held as long as the session is open
+///
+/// the lock lives in a separate file, because the vault file itself gets replaced on each write
+#[derive(Debug)]
+pub struct VaultLock {
+  file: File,
+}
+
+impl VaultLock {
+  /// try to lock the vault of the user, returns `None` if another session holds the lock
+  pub fn acquire(directory: &Path, username: &str) -> io::Result<Option<Self>> {
+    let file = OpenOptions::new()
+      .create(true)
+      .write(true)
+      // another session may hold the lock, the file is never truncated
+      .truncate(false)
+      .open(
+        directory
+          .join(username.to_lowercase())
+          .with_extension("lock"),
+      )?;
+
+    match file.try_lock_exclusive() {
+      Ok(()) => Ok(Some(Self { file })),
+      Err(error) if error.kind() == fs2::lock_contended_error().kind() => Ok(None),
+      Err(error) => Err(error),
+    }
+  }
+}
+
+impl Drop for VaultLock {
+  fn drop(&mut self) {
+    // closing the file would release it too
+    let _ = self.file.unlock();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  #[test]
+  fn test_exclusive() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+
+    let lock = VaultLock::acquire(&path, "Username").unwrap();
+    assert!(lock.is_some());
+    assert!(VaultLock::acquire(&path, "username").unwrap().is_none());
+
+    // released on drop
+    drop(lock);
+    assert!(VaultLock::acquire(&path, "username").unwrap().is_some());
+  }
+}
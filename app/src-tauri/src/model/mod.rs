@@ -23,7 +23,22 @@
  * SOFTWARE.
  */
 
+pub mod attachment;
 pub mod backup;
 pub mod encryption;
+pub mod field;
+pub mod folder;
 pub mod generator;
+pub mod history;
+pub mod import;
+pub mod index;
+pub mod item;
+pub mod kdf;
+pub mod lock;
+pub mod otp;
+pub mod search;
+pub mod session;
+pub mod snapshot;
+pub mod sync;
 pub mod user;
+pub mod util;
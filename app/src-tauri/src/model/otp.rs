@@ -0,0 +1,408 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use hmac::{digest::KeyInit, Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use url::Url;
+use zeroize::Zeroizing;
+
+const BASE32: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const STEAM_ALPHABET: &[u8] = b"23456789BCDFGHJKMNPQRTVWXY";
+
+#[derive(Error, Debug)]
+pub enum OtpError {
+  #[error("Invalid otp uri")]
+  UriError,
+
+  #[error("Invalid base32 secret")]
+  SecretError,
+
+  #[error("Unsupported otp parameter {0}")]
+  ParameterError(String),
+}
+
+type Result<T> = std::result::Result<T, OtpError>;
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OtpKind {
+  Totp,
+  Hotp,
+  // totp with 5 characters of the steam alphabet
+  Steam,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Algorithm {
+  Sha1,
+  Sha256,
+  Sha512,
+}
+
+/// the current code of an entry
+#[derive(Deserialize, Serialize, Debug)]
+pub struct OtpCode {
+  code: String,
+  kind: OtpKind,
+  // seconds until the code changes, none for hotp
+  remaining: Option<u64>,
+  period: Option<u64>,
+}
+
+impl OtpCode {
+  pub fn code(&self) -> &str {
+    self.code.as_str()
+  }
+}
+
+/// parsed otp configuration, stored as uri or plain base32 secret on the entries
+pub struct Otp {
+  kind: OtpKind,
+  secret: Zeroizing<Vec<u8>>,
+  algorithm: Algorithm,
+  digits: u32,
+  period: u64,
+  counter: u64,
+}
+
+impl Otp {
+  /// parse an `otpauth://`, a `steam://` uri or a plain base32 secret
+  pub fn parse(input: &str) -> Result<Self> {
+    let input = input.trim();
+    if let Some(secret) = input.strip_prefix("steam://") {
+      return Ok(Self::steam(decode_base32(secret)?));
+    }
+    if !input.starts_with("otpauth://") {
+      return Ok(Self::totp(decode_base32(input)?));
+    }
+
+    let url = Url::parse(input).map_err(|_| OtpError::UriError)?;
+    let mut otp = match url.host_str() {
+      Some("totp") => Self::totp(Vec::new()),
+      Some("hotp") => Self {
+        kind: OtpKind::Hotp,
+        ..Self::totp(Vec::new())
+      },
+      _ => return Err(OtpError::UriError),
+    };
+
+    let mut secret = None;
+    for (key, value) in url.query_pairs() {
+      let invalid = || OtpError::ParameterError(key.to_string());
+      match key.as_ref() {
+        "secret" => secret = Some(decode_base32(value.as_ref())?),
+        "algorithm" => {
+          otp.algorithm = match value.to_uppercase().as_str() {
+            "SHA1" => Algorithm::Sha1,
+            "SHA256" => Algorithm::Sha256,
+            "SHA512" => Algorithm::Sha512,
+            _ => return Err(invalid()),
+          }
+        }
+        "digits" => {
+          otp.digits = value.parse().map_err(|_| invalid())?;
+          if !(6..=8).contains(&otp.digits) {
+            return Err(invalid());
+          }
+        }
+        "period" => {
+          otp.period = value.parse().map_err(|_| invalid())?;
+          if otp.period == 0 {
+            return Err(invalid());
+          }
+        }
+        "counter" => otp.counter = value.parse().map_err(|_| invalid())?,
+        // used by some exports for steam guard
+        "encoder" if value.eq_ignore_ascii_case("steam") => otp.kind = OtpKind::Steam,
+        // label, issuer, image and others are not needed
+        _ => {}
+      }
+    }
+
+    // steam codes always have 5 characters
+    if otp.kind == OtpKind::Steam {
+      otp.digits = 5;
+    }
+    otp.secret = Zeroizing::new(secret.ok_or(OtpError::SecretError)?);
+    Ok(otp)
+  }
+
+  fn totp(secret: Vec<u8>) -> Self {
+    Self {
+      kind: OtpKind::Totp,
+      secret: Zeroizing::new(secret),
+      algorithm: Algorithm::Sha1,
+      digits: 6,
+      period: 30,
+      counter: 0,
+    }
+  }
+
+  fn steam(secret: Vec<u8>) -> Self {
+    Self {
+      kind: OtpKind::Steam,
+      digits: 5,
+      ..Self::totp(secret)
+    }
+  }
+
+  pub fn kind(&self) -> OtpKind {
+    self.kind
+  }
+
+  /// the code at the current time or counter
+  pub fn now(&self) -> OtpCode {
+    let time = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .unwrap()
+      .as_secs();
+    self.at(time)
+  }
+
+  /// the code at the given unix time, hotp ignores the time
+  pub fn at(&self, time: u64) -> OtpCode {
+    match self.kind {
+      OtpKind::Hotp => OtpCode {
+        code: self.hotp(self.counter),
+        kind: self.kind,
+        remaining: None,
+        period: None,
+      },
+      _ => OtpCode {
+        code: self.hotp(time / self.period),
+        kind: self.kind,
+        remaining: Some(self.period - time % self.period),
+        period: Some(self.period),
+      },
+    }
+  }
+
+  /// rfc 4226 code for the counter
+  fn hotp(&self, counter: u64) -> String {
+    let message = counter.to_be_bytes();
+    let hash = match self.algorithm {
+      Algorithm::Sha1 => sign::<Hmac<Sha1>>(&self.secret, &message),
+      Algorithm::Sha256 => sign::<Hmac<Sha256>>(&self.secret, &message),
+      Algorithm::Sha512 => sign::<Hmac<Sha512>>(&self.secret, &message),
+    };
+
+    // dynamic truncation
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let mut value = u32::from_be_bytes([
+      hash[offset],
+      hash[offset + 1],
+      hash[offset + 2],
+      hash[offset + 3],
+    ]) & 0x7fff_ffff;
+
+    match self.kind {
+      OtpKind::Steam => (0..self.digits)
+        .map(|_| {
+          let character = STEAM_ALPHABET[(value % STEAM_ALPHABET.len() as u32) as usize];
+          value /= STEAM_ALPHABET.len() as u32;
+          character as char
+        })
+        .collect(),
+      _ => format!(
+        "{:0width$}",
+        value % 10u32.pow(self.digits),
+        width = self.digits as usize
+      ),
+    }
+  }
+}
+
+/// increase the counter of a hotp uri by one
+pub fn increment_counter(input: &str) -> Result<String> {
+  let otp = Otp::parse(input)?;
+  if otp.kind != OtpKind::Hotp {
+    return Err(OtpError::ParameterError(String::from("counter")));
+  }
+
+  let counter = otp
+    .counter
+    .checked_add(1)
+    .ok_or_else(|| OtpError::ParameterError(String::from("counter")))?;
+
+  let mut url = Url::parse(input.trim()).map_err(|_| OtpError::UriError)?;
+  let pairs = url
+    .query_pairs()
+    .filter(|(key, _)| key != "counter")
+    .map(|(key, value)| (key.to_string(), value.to_string()))
+    .collect::<Vec<(String, String)>>();
+  url
+    .query_pairs_mut()
+    .clear()
+    .extend_pairs(pairs)
+    .append_pair("counter", counter.to_string().as_str());
+
+  Ok(url.to_string())
+}
+
+fn sign<M: Mac + KeyInit>(key: &[u8], message: &[u8]) -> Vec<u8> {
+  // hmac accepts keys of any length
+  let mut mac = <M as Mac>::new_from_slice(key).unwrap();
+  mac.update(message);
+  mac.finalize().into_bytes().to_vec()
+}
+
+/// decode rfc 4648 base32, ignoring case, spaces and padding
+fn decode_base32(input: &str) -> Result<Vec<u8>> {
+  let mut buffer: u64 = 0;
+  let mut bits = 0;
+  let mut output = Vec::new();
+  for character in input.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+    let value = BASE32
+      .find(character.to_ascii_uppercase())
+      .ok_or(OtpError::SecretError)?;
+    buffer = (buffer << 5) | value as u64;
+    bits += 5;
+    if bits >= 8 {
+      bits -= 8;
+      output.push((buffer >> bits) as u8);
+      buffer &= (1 << bits) - 1;
+    }
+  }
+
+  match output.is_empty() {
+    true => Err(OtpError::SecretError),
+    false => Ok(output),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // the ascii secrets of rfc 6238, base32 encoded
+  const SHA1_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+  const SHA256_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZA====";
+  const SHA512_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ\
+    GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNA=";
+
+  #[test]
+  fn test_rfc6238() {
+    let vectors = [
+      (59, "94287082", "46119246", "90693936"),
+      (1111111109, "07081804", "68084774", "25091201"),
+      (1111111111, "14050471", "67062674", "99943326"),
+      (1234567890, "89005924", "91819424", "93441116"),
+      (2000000000, "69279037", "90698825", "38618901"),
+      (20000000000, "65353130", "77737706", "47863826"),
+    ];
+    let otp = |algorithm: &str, secret: &str| {
+      Otp::parse(
+        format!(
+          "otpauth://totp/Example:alice?secret={}&algorithm={}&digits=8&period=30",
+          secret.replace('=', ""),
+          algorithm
+        )
+        .as_str(),
+      )
+      .unwrap()
+    };
+    let sha1 = otp("SHA1", SHA1_SECRET);
+    let sha256 = otp("SHA256", SHA256_SECRET);
+    let sha512 = otp("SHA512", SHA512_SECRET);
+
+    for (time, first, second, third) in vectors.iter() {
+      assert_eq!(*first, sha1.at(*time).code());
+      assert_eq!(*second, sha256.at(*time).code());
+      assert_eq!(*third, sha512.at(*time).code());
+    }
+  }
+
+  #[test]
+  fn test_rfc4226() {
+    let vectors = [
+      "755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583", "399871",
+      "520489",
+    ];
+
+    let mut uri = format!("otpauth://hotp/Example?secret={}&counter=0", SHA1_SECRET);
+    for expected in vectors.iter() {
+      let otp = Otp::parse(uri.as_str()).unwrap();
+      assert_eq!(*expected, otp.at(0).code());
+      uri = increment_counter(uri.as_str()).unwrap();
+    }
+  }
+
+  #[test]
+  fn test_counter_overflow() {
+    let uri = format!(
+      "otpauth://hotp/Example?secret={}&counter={}",
+      SHA1_SECRET,
+      u64::MAX
+    );
+    assert!(matches!(
+      increment_counter(uri.as_str()),
+      Err(OtpError::ParameterError(_))
+    ));
+  }
+
+  #[test]
+  fn test_plain_secret() {
+    let otp = Otp::parse("gezd gnbv gy3t qojq gezd gnbv gy3t qojq").unwrap();
+    assert_eq!(OtpKind::Totp, otp.kind());
+
+    let code = otp.at(59);
+    assert_eq!("287082", code.code());
+    assert_eq!(Some(1), code.remaining);
+  }
+
+  #[test]
+  fn test_steam() {
+    let otp = Otp::parse("steam://JBSWY3DPEHPK3PXP").unwrap();
+    assert_eq!("2YXGV", otp.at(30).code());
+
+    let otp =
+      Otp::parse("otpauth://totp/Steam:alice?secret=JBSWY3DPEHPK3PXP&encoder=steam").unwrap();
+    assert_eq!("2YXGV", otp.at(59).code());
+  }
+
+  #[test]
+  fn test_invalid() {
+    assert!(matches!(
+      Otp::parse("not base32!"),
+      Err(OtpError::SecretError)
+    ));
+    assert!(matches!(
+      Otp::parse("otpauth://totp/Example?secret=JBSWY3DPEHPK3PXP&digits=12"),
+      Err(OtpError::ParameterError(_))
+    ));
+    assert!(matches!(
+      Otp::parse("otpauth://other/Example?secret=JBSWY3DPEHPK3PXP"),
+      Err(OtpError::UriError)
+    ));
+    assert!(matches!(
+      Otp::parse("otpauth://totp/Example"),
+      Err(OtpError::SecretError)
+    ));
+  }
+}
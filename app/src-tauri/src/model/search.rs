@@ -0,0 +1,135 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::model::item::ItemKind;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+  // best matches first, by name without a query
+  #[default]
+  Relevance,
+  Name,
+  // the timestamps are sorted newest first
+  Created,
+  Modified,
+  LastUsed,
+}
+
+/// filters of the search, every filter is optional
+#[derive(Deserialize, Serialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct SearchQuery {
+  pub query: String,
+  // includes the subfolders
+  pub folder: Option<String>,
+  pub kind: Option<ItemKind>,
+  pub favorite: bool,
+  // weak and very weak passwords
+  pub weak: bool,
+  pub reused: bool,
+  // entries with an otp secret
+  pub otp: bool,
+  pub sort: SortOrder,
+}
+
+// characters of a fuzzy match may be spread over this many characters per query character
+const FUZZY_SPREAD: usize = 3;
+
+/// score how well the word matches the text, none if it does not match at all
+fn fuzzy_score(word: &str, text: &str) -> Option<u32> {
+  let text = text.to_lowercase();
+  // exact matches first, then prefixes and substrings
+  if let Some(position) = text.find(word) {
+    return Some(match position {
+      _ if text.len() == word.len() => 300,
+      0 => 200,
+      _ => 100,
+    });
+  }
+
+  // all characters in order, consecutive ones score higher
+  let text = text.chars().collect::<Vec<char>>();
+  let mut score = 0;
+  let mut first = None;
+  let mut last: Option<usize> = None;
+  for character in word.chars() {
+    let start = last.map_or(0, |last| last + 1);
+    let index = start + text[start..].iter().position(|c| *c == character)?;
+    score += match last {
+      Some(last) if last + 1 == index => 5,
+      _ => 1,
+    };
+    first = first.or(Some(index));
+    last = Some(index);
+  }
+
+  // characters spread over a long text are no match
+  let span = last? - first? + 1;
+  match span <= word.chars().count() * FUZZY_SPREAD {
+    true => Some(score),
+    false => None,
+  }
+}
+
+/// score the query against the terms of an entry, every word of the query has to match
+pub fn score(query: &str, terms: &[&str]) -> Option<u32> {
+  query
+    .to_lowercase()
+    .split_whitespace()
+    .try_fold(0, |total, word| {
+      terms
+        .iter()
+        .filter_map(|term| fuzzy_score(word, term))
+        .max()
+        .map(|best| total + best)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_fuzzy_score() {
+    assert_eq!(Some(300), fuzzy_score("github", "GitHub"));
+    assert_eq!(Some(200), fuzzy_score("git", "GitHub"));
+    assert_eq!(Some(100), fuzzy_score("hub", "GitHub"));
+    // g, t, h, b in order
+    assert_eq!(Some(8), fuzzy_score("gthb", "github"));
+    assert_eq!(None, fuzzy_score("bug", "github"));
+    // too far apart
+    assert_eq!(None, fuzzy_score("ab", "a long text with a b"));
+  }
+
+  #[test]
+  fn test_score() {
+    let terms = ["GitHub", "alice@example.com", "https://github.com"];
+    assert_eq!(Some(500), score("github alice", &terms));
+    assert_eq!(None, score("github bob", &terms));
+    assert_eq!(Some(0), score("", &terms));
+  }
+}
@@ -0,0 +1,198 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant, SystemTime};
+
+// difference between wall clock and monotonic clock which is seen as a suspended system
+const SUSPEND_THRESHOLD: Duration = Duration::from_secs(30);
+
+/// auto lock settings, saved with the vault
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct AutoLock {
+  // idle seconds until the vault gets locked, 0 disables the timeout
+  timeout: u64,
+  // lock after the system has been suspended
+  suspend: bool,
+}
+
+impl Default for AutoLock {
+  fn default() -> Self {
+    Self {
+      timeout: 300,
+      suspend: true,
+    }
+  }
+}
+
+/// activity of the current session, tracked on every command
+pub struct Session {
+  // kept after locking, so unlocking only needs the password
+  username: Option<String>,
+  // only available while the vault is unlocked
+  auto_lock: Option<AutoLock>,
+  last_activity: Instant,
+  // both clocks at the last check, only the wall clock keeps running while suspended
+  last_check: (Instant, SystemTime),
+}
+
+impl Default for Session {
+  fn default() -> Self {
+    Self {
+      username: None,
+      auto_lock: None,
+      last_activity: Instant::now(),
+      last_check: (Instant::now(), SystemTime::now()),
+    }
+  }
+}
+
+impl Session {
+  /// start tracking an unlocked vault
+  pub fn start(&mut self, username: String, auto_lock: AutoLock) {
+    self.username = Some(username);
+    self.update(auto_lock);
+    self.touch();
+    // a suspend before the unlock does not matter
+    self.last_check = (self.last_activity, SystemTime::now());
+  }
+
+  /// stop tracking, but keep the username selected
+  pub fn lock(&mut self) {
+    self.auto_lock = None;
+  }
+
+  /// stop tracking and forget the username
+  pub fn stop(&mut self) {
+    self.lock();
+    self.username = None;
+  }
+
+  /// the username of the current or last unlocked vault
+  pub fn username(&self) -> Option<String> {
+    self.username.clone()
+  }
+
+  /// check whether the vault is unlocked
+  pub fn active(&self) -> bool {
+    self.auto_lock.is_some()
+  }
+
+  /// replace the settings of the running session
+  pub fn update(&mut self, auto_lock: AutoLock) {
+    self.auto_lock = Some(auto_lock);
+  }
+
+  /// register activity
+  pub fn touch(&mut self) {
+    self.last_activity = Instant::now();
+  }
+
+  /// check whether the vault should be locked now
+  pub fn expired(&mut self) -> bool {
+    self.expired_at(Instant::now(), SystemTime::now())
+  }
+
+  fn expired_at(&mut self, now: Instant, wall: SystemTime) -> bool {
+    let (last_instant, last_wall) = std::mem::replace(&mut self.last_check, (now, wall));
+    let auto_lock = match &self.auto_lock {
+      Some(auto_lock) => auto_lock,
+      None => return false,
+    };
+
+    // the monotonic clock stops while the system sleeps
+    let slept = wall
+      .duration_since(last_wall)
+      .unwrap_or_default()
+      .saturating_sub(now.saturating_duration_since(last_instant));
+    if auto_lock.suspend && slept > SUSPEND_THRESHOLD {
+      return true;
+    }
+
+    auto_lock.timeout > 0
+      && now.saturating_duration_since(self.last_activity) >= Duration::from_secs(auto_lock.timeout)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_timeout() {
+    let mut session = Session::default();
+    // nothing to lock
+    assert!(!session.expired());
+
+    session.start(String::from("username"), AutoLock::default());
+    let (now, wall) = session.last_check;
+    assert!(!session.expired_at(
+      now + Duration::from_secs(299),
+      wall + Duration::from_secs(299)
+    ));
+    assert!(session.expired_at(
+      now + Duration::from_secs(300),
+      wall + Duration::from_secs(300)
+    ));
+
+    // disabled timeout
+    session.update(AutoLock {
+      timeout: 0,
+      suspend: true,
+    });
+    assert!(!session.expired_at(
+      now + Duration::from_secs(900),
+      wall + Duration::from_secs(900)
+    ));
+
+    session.lock();
+    assert!(!session.active());
+    assert_eq!(Some(String::from("username")), session.username());
+    session.stop();
+    assert_eq!(None, session.username());
+  }
+
+  #[test]
+  fn test_suspend() {
+    let mut session = Session::default();
+    session.start(String::from("username"), AutoLock::default());
+    let (now, wall) = session.last_check;
+
+    // only the wall clock kept running
+    assert!(session.expired_at(
+      now + Duration::from_secs(1),
+      wall + Duration::from_secs(120)
+    ));
+
+    session.update(AutoLock {
+      timeout: 300,
+      suspend: false,
+    });
+    assert!(!session.expired_at(
+      now + Duration::from_secs(2),
+      wall + Duration::from_secs(240)
+    ));
+  }
+}
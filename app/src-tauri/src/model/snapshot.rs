@@ -0,0 +1,207 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::{
+  fs, io,
+  path::{Path, PathBuf},
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+/// how many snapshots are kept per user
+const SNAPSHOT_COUNT: usize = 10;
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Snapshot {
+  // name of the file, sortable by the creation
+  id: String,
+  // unix timestamp in milliseconds
+  created: u64,
+}
+
+/// rolling copies of the encrypted vault file
+pub struct Snapshots {
+  directory: PathBuf,
+}
+
+impl Snapshot {
+  pub fn id(&self) -> String {
+    self.id.clone()
+  }
+}
+
+impl Snapshots {
+  /// the snapshots of the user are stored in `snapshots/<username>`
+  pub fn new(directory: &Path, username: &str) -> Self {
+    Self {
+      directory: directory.join("snapshots").join(username.to_lowercase()),
+    }
+  }
+
+  /// copy the current file into a new snapshot and drop the oldest ones
+  pub fn create(&self, file: &PathBuf) -> io::Result<()> {
+    // nothing to save on the first write
+    if !file.exists() {
+      return Ok(());
+    }
+    fs::create_dir_all(&self.directory)?;
+
+    let nanos = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .unwrap()
+      .as_nanos();
+    // zero padded, so the names sort like the timestamps
+    let id = format!("{:024}", nanos);
+    let path = self.path(id.as_str());
+    fs::copy(file, &path)?;
+    // the copy has to be on the disk before the vault gets replaced
+    fs::OpenOptions::new().write(true).open(&path)?.sync_all()?;
+    #[cfg(unix)]
+    fs::File::open(&self.directory)?.sync_all()?;
+
+    self.prune()
+  }
+
+  /// remove all snapshots, e.g. after they have been encrypted with a replaced key
+  pub fn clear(&self) -> io::Result<()> {
+    for snapshot in self.list()? {
+      fs::remove_file(self.path(snapshot.id.as_str()))?;
+    }
+    Ok(())
+  }
+
+  /// list all snapshots, newest first
+  pub fn list(&self) -> io::Result<Vec<Snapshot>> {
+    if !self.directory.exists() {
+      return Ok(Vec::new());
+    }
+
+    let mut snapshots = fs::read_dir(&self.directory)?
+      .filter_map(|entry| entry.ok())
+      .filter_map(|entry| {
+        let path = entry.path();
+        let id = path.file_stem()?.to_str()?.to_string();
+        // ignore everything we did not create
+        let nanos = id.parse::<u128>().ok()?;
+
+        Some(Snapshot {
+          id,
+          created: (nanos / 1_000_000) as u64,
+        })
+      })
+      .collect::<Vec<Snapshot>>();
+    snapshots.sort_by(|a, b| b.id.cmp(&a.id));
+
+    Ok(snapshots)
+  }
+
+  /// read the content of a snapshot
+  pub fn read(&self, id: &str) -> io::Result<String> {
+    // the id is used as filename
+    if id.is_empty() || !id.chars().all(|char| char.is_ascii_digit()) {
+      return Err(io::Error::new(io::ErrorKind::NotFound, "Unknown snapshot"));
+    }
+
+    fs::read_to_string(self.path(id))
+  }
+
+  fn path(&self, id: &str) -> PathBuf {
+    self.directory.join(id).with_extension("json")
+  }
+
+  /// remove everything but the newest snapshots
+  fn prune(&self) -> io::Result<()> {
+    for snapshot in self.list()?.iter().skip(SNAPSHOT_COUNT) {
+      fs::remove_file(self.path(snapshot.id.as_str()))?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  #[test]
+  fn test_first_write() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let snapshots = Snapshots::new(&path, "Username");
+
+    snapshots.create(&path.join("username.json")).unwrap();
+    assert!(snapshots.list().unwrap().is_empty());
+  }
+
+  #[test]
+  fn test_rotation() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let file = path.join("username.json");
+    let snapshots = Snapshots::new(&path, "Username");
+
+    for i in 0..SNAPSHOT_COUNT + 2 {
+      fs::write(&file, i.to_string()).unwrap();
+      snapshots.create(&file).unwrap();
+    }
+
+    let list = snapshots.list().unwrap();
+    assert_eq!(SNAPSHOT_COUNT, list.len());
+    // newest first
+    assert_eq!(
+      (SNAPSHOT_COUNT + 1).to_string(),
+      snapshots.read(list[0].id.as_str()).unwrap()
+    );
+    assert_eq!(
+      "2",
+      snapshots.read(list.last().unwrap().id.as_str()).unwrap()
+    );
+  }
+
+  #[test]
+  fn test_clear() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let file = path.join("username.json");
+    let snapshots = Snapshots::new(&path, "username");
+    fs::write(&file, "vault").unwrap();
+    snapshots.create(&file).unwrap();
+    snapshots.create(&file).unwrap();
+
+    snapshots.clear().unwrap();
+    assert!(snapshots.list().unwrap().is_empty());
+    // nothing to clear
+    snapshots.clear().unwrap();
+  }
+
+  #[test]
+  fn test_invalid_id() {
+    let dir = TempDir::new().unwrap();
+    let snapshots = Snapshots::new(dir.as_ref(), "username");
+
+    assert!(snapshots.read("../username").is_err());
+    assert!(snapshots.read("").is_err());
+  }
+}
@@ -0,0 +1,347 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SyncError {
+  #[error(transparent)]
+  IOError(#[from] std::io::Error),
+
+  #[error(transparent)]
+  ParseError(#[from] serde_json::Error),
+
+  #[error("Transport error: {0}")]
+  TransportError(String),
+
+  #[error("Unexpected status {0}")]
+  StatusError(u16),
+
+  #[error("Unauthorized")]
+  Unauthorized,
+}
+
+impl From<ureq::Error> for SyncError {
+  fn from(error: ureq::Error) -> Self {
+    match error {
+      // the worker does not differ between unknown uuid and wrong password
+      ureq::Error::Status(401, _) => SyncError::Unauthorized,
+      ureq::Error::Status(status, _) => SyncError::StatusError(status),
+      ureq::Error::Transport(transport) => SyncError::TransportError(transport.to_string()),
+    }
+  }
+}
+
+type Result<T> = std::result::Result<T, SyncError>;
+
+#[derive(Deserialize, Serialize)]
+struct Login {
+  uuid: String,
+  password: String,
+}
+
+#[derive(Deserialize, Serialize)]
+struct SignUp {
+  password: String,
+}
+
+#[derive(Deserialize)]
+struct Account {
+  uuid: String,
+}
+
+#[derive(Deserialize)]
+struct Token {
+  token: String,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Data {
+  data: String,
+}
+
+/// http client for the backup worker
+pub struct SyncClient {
+  // base url of the worker, without trailing slash
+  url: String,
+  // the jwt, only available after the login
+  token: Option<String>,
+  agent: ureq::Agent,
+}
+
+impl SyncClient {
+  /// create new client for the given worker
+  pub fn new(url: &str) -> Self {
+    Self {
+      url: url.trim_end_matches('/').to_string(),
+      token: None,
+      agent: ureq::Agent::new(),
+    }
+  }
+
+  /// build the full url of an endpoint
+  fn endpoint(&self, path: &str) -> String {
+    format!("{}{}", self.url, path)
+  }
+
+  /// the token of the current session
+  fn token(&self) -> Result<&str> {
+    match &self.token {
+      Some(token) => Ok(token.as_str()),
+      None => Err(SyncError::Unauthorized),
+    }
+  }
+
+  /// create a new backup account and return its uuid
+  pub fn signup(&self, password: &str) -> Result<String> {
+    let account = self
+      .agent
+      .post(self.endpoint("/auth/signup").as_str())
+      .send_json(serde_json::to_value(SignUp {
+        password: password.to_string(),
+      })?)?
+      .into_json::<Account>()?;
+
+    Ok(account.uuid)
+  }
+
+  /// start a new session on the worker
+  pub fn login(&mut self, uuid: &str, password: &str) -> Result<()> {
+    let token = self
+      .agent
+      .post(self.endpoint("/auth/login").as_str())
+      .send_json(serde_json::to_value(Login {
+        uuid: uuid.to_string(),
+        password: password.to_string(),
+      })?)?
+      .into_json::<Token>()?;
+
+    self.token = Some(token.token);
+    Ok(())
+  }
+
+  /// get the saved data blob, none if nothing has been pushed yet
+  pub fn pull(&self) -> Result<Option<String>> {
+    let response = self
+      .agent
+      .get(self.endpoint("/user/data").as_str())
+      .set("Authorization", self.token()?)
+      .call();
+    let data = match response {
+      Ok(response) => response.into_json::<Data>()?,
+      Err(ureq::Error::Status(404, _)) => return Ok(None),
+      Err(error) => return Err(error.into()),
+    };
+
+    Ok(Some(data.data).filter(|data| !data.is_empty()))
+  }
+
+  /// overwrite the saved data blob
+  pub fn push(&self, data: &str) -> Result<()> {
+    self
+      .agent
+      .post(self.endpoint("/user/data").as_str())
+      .set("Authorization", self.token()?)
+      .send_json(serde_json::to_value(Data {
+        data: data.to_string(),
+      })?)?;
+
+    Ok(())
+  }
+}
+
+/// minimal stand-in for the worker, only used by the tests
+#[cfg(test)]
+pub mod testing {
+  use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+  };
+
+  // uuid -> (password, data)
+  type Accounts = Arc<Mutex<HashMap<String, (String, String)>>>;
+
+  pub struct MockWorker {
+    pub url: String,
+    accounts: Accounts,
+  }
+
+  impl MockWorker {
+    /// bind to a random local port and serve in the background
+    pub fn start() -> Self {
+      let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+      let url = format!("http://{}", listener.local_addr().unwrap());
+      let accounts: Accounts = Arc::new(Mutex::new(HashMap::new()));
+
+      let cloned = accounts.clone();
+      thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+          Self::handle(stream, &cloned);
+        }
+      });
+
+      Self { url, accounts }
+    }
+
+    /// get the raw data saved for an account
+    pub fn data(&self, uuid: &str) -> Option<String> {
+      self
+        .accounts
+        .lock()
+        .unwrap()
+        .get(uuid)
+        .map(|(_, data)| data.clone())
+    }
+
+    fn handle(stream: TcpStream, accounts: &Accounts) {
+      let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+      // request line
+      let mut line = String::new();
+      reader.read_line(&mut line).unwrap();
+      let mut parts = line.split_whitespace();
+      let method = parts.next().unwrap_or("").to_string();
+      let path = parts.next().unwrap_or("").to_string();
+
+      // headers
+      let mut length = 0;
+      let mut token = None;
+      loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).unwrap();
+        let header = header.trim_end();
+        if header.is_empty() {
+          break;
+        }
+        let (name, value) = header.split_once(':').unwrap();
+        match name.to_lowercase().as_str() {
+          "content-length" => length = value.trim().parse().unwrap(),
+          "authorization" => token = Some(value.trim().to_string()),
+          _ => {}
+        }
+      }
+
+      // body
+      let mut body = vec![0; length];
+      reader.read_exact(&mut body).unwrap();
+      let body = serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default();
+
+      let mut accounts = accounts.lock().unwrap();
+      // tokens are simply the uuid prefixed
+      let session = token
+        .and_then(|token| token.strip_prefix("token-").map(str::to_string))
+        .filter(|uuid| accounts.contains_key(uuid));
+
+      let (status, response) = match (method.as_str(), path.as_str(), session) {
+        ("POST", "/auth/signup", _) => {
+          let uuid = uuid::Uuid::new_v4().to_string();
+          let password = body["password"].as_str().unwrap().to_string();
+          accounts.insert(uuid.clone(), (password.clone(), String::new()));
+          (
+            200,
+            serde_json::json!({ "uuid": uuid, "password": password, "data": "" }),
+          )
+        }
+        ("POST", "/auth/login", _) => {
+          let uuid = body["uuid"].as_str().unwrap();
+          match accounts.get(uuid) {
+            Some((password, _)) if password.eq(body["password"].as_str().unwrap()) => (
+              200,
+              serde_json::json!({ "token": format!("token-{}", uuid) }),
+            ),
+            _ => (401, serde_json::json!("Unauthorized")),
+          }
+        }
+        ("GET", "/user/data", Some(uuid)) => {
+          (200, serde_json::json!({ "data": accounts[&uuid].1 }))
+        }
+        ("POST", "/user/data", Some(uuid)) => {
+          accounts.get_mut(&uuid).unwrap().1 = body["data"].as_str().unwrap().to_string();
+          (200, serde_json::json!("Updated"))
+        }
+        _ => (401, serde_json::json!("Unauthorized")),
+      };
+
+      let response = response.to_string();
+      let mut stream = reader.into_inner();
+      write!(
+        stream,
+        "HTTP/1.1 {} OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        response.len(),
+        response
+      )
+      .unwrap();
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{testing::MockWorker, *};
+
+  #[test]
+  fn test_signup_login() {
+    let worker = MockWorker::start();
+    let mut client = SyncClient::new(worker.url.as_str());
+
+    let uuid = client.signup("secret").unwrap();
+    client.login(uuid.as_str(), "secret").unwrap();
+  }
+
+  #[test]
+  fn test_wrong_password() {
+    let worker = MockWorker::start();
+    let mut client = SyncClient::new(worker.url.as_str());
+
+    let uuid = client.signup("secret").unwrap();
+    assert!(matches!(
+      client.login(uuid.as_str(), "wrong"),
+      Err(SyncError::Unauthorized)
+    ));
+  }
+
+  #[test]
+  fn test_push_pull() {
+    let worker = MockWorker::start();
+    let mut client = SyncClient::new(format!("{}/", worker.url).as_str());
+
+    // no session yet
+    assert!(matches!(client.push("data"), Err(SyncError::Unauthorized)));
+
+    let uuid = client.signup("secret").unwrap();
+    client.login(uuid.as_str(), "secret").unwrap();
+    // a new account has no data
+    assert_eq!(None, client.pull().unwrap());
+    client.push("data").unwrap();
+
+    assert_eq!(Some(String::from("data")), client.pull().unwrap());
+    assert_eq!(worker.data(uuid.as_str()).unwrap(), "data");
+  }
+}
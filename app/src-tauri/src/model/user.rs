@@ -24,18 +24,46 @@
  */
 
 use crate::model::{
-  backup::Backup,
-  encryption::{Encryption, EncryptionError},
+  attachment::{Attachment, Attachments, MAX_ATTACHMENT_SIZE},
+  backup::{Backup, BackupStatus},
+  encryption::{CipherText, Encryption, EncryptionError},
+  field::{CustomField, FieldError, FieldKind},
+  folder::{self, Folder},
   generator::PasswordGenerator,
+  history::{self, HistoryEntry},
+  import::{self, Import, ImportReport},
+  index::{IndexError, VaultIndex},
+  item::{Item, ItemKind},
+  kdf::Kdf,
+  lock::VaultLock,
+  otp::{self, Otp, OtpCode, OtpError},
+  search::{self, SearchQuery, SortOrder},
+  session::AutoLock,
+  snapshot::{Snapshot, Snapshots},
+  sync::{SyncClient, SyncError},
+  util::{self, DAY},
 };
 use pbkdf2::{
-  password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+  password_hash::{
+    self, rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+  },
   Pbkdf2,
 };
-use serde::{Deserialize, Serialize};
-use std::{fs, path::PathBuf};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+  cell::{Cell, RefCell},
+  cmp::Reverse,
+  collections::{hash_map::DefaultHasher, BTreeMap, HashMap, HashSet},
+  fmt, fs,
+  hash::{Hash, Hasher},
+  io::{self, ErrorKind, Write},
+  path::{Path, PathBuf},
+};
+use tempfile::NamedTempFile;
 use thiserror::Error;
 use uuid::Uuid;
+use zeroize::{Zeroize, Zeroizing};
 
 #[derive(Deserialize, Serialize)]
 pub struct AnalyseResult {
@@ -46,6 +74,8 @@ pub struct AnalyseResult {
   medium: Vec<String>,
   weak: Vec<String>,
   very_weak: Vec<String>,
+  // passwords which have not been changed for longer than the maximum age
+  old: Vec<String>,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
@@ -54,6 +84,18 @@ pub struct UserData {
   password: String,
 }
 
+impl UserData {
+  pub fn new(username: String, password: String) -> Self {
+    Self { username, password }
+  }
+}
+
+impl Drop for UserData {
+  fn drop(&mut self) {
+    self.password.zeroize();
+  }
+}
+
 #[derive(Error, Debug)]
 pub enum ConfigError {
   #[error(transparent)]
@@ -65,11 +107,59 @@ pub enum ConfigError {
   #[error(transparent)]
   EncryptionError(#[from] EncryptionError),
 
+  #[error(transparent)]
+  SyncError(#[from] SyncError),
+
+  #[error(transparent)]
+  FieldError(#[from] FieldError),
+
+  #[error(transparent)]
+  OtpError(#[from] OtpError),
+
+  #[error(transparent)]
+  HashError(#[from] password_hash::Error),
+
+  #[error(transparent)]
+  IndexError(#[from] IndexError),
+
+  #[error("Invalid vault format")]
+  FormatError,
+
+  #[error("Unsupported vault version {0}")]
+  VersionError(u64),
+
+  #[error("Vault is opened by another session")]
+  LockedError,
+
+  #[error("Vault has been modified by another session")]
+  ConflictError,
+
+  #[error("User already exists")]
+  UserExistsError,
+
+  #[error("Backup is not enabled")]
+  BackupError,
+
+  #[error("Nothing has been pushed to the backup yet")]
+  EmptyBackupError,
+
+  #[error("Vault contains corrupt entries")]
+  CorruptError,
+
+  #[error("Not found")]
+  NotFoundError,
+
+  #[error("Attachment is too large")]
+  AttachmentSizeError,
+
+  #[error("Folder can not be moved into itself")]
+  FolderCycleError,
+
   #[error("Unauthorized")]
   Unknown,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone)]
 // complete serde::to_string() and aes encrypted on the disk (base64)
 pub struct PasswordData {
   name: Option<String>,
@@ -77,8 +167,175 @@ pub struct PasswordData {
   password: Option<String>,
   url: Option<String>,
   description: Option<String>,
+  // uuid of the folder, none for the top level
+  #[serde(default)]
+  folder: Option<String>,
+  #[serde(default)]
+  tags: Vec<String>,
+  #[serde(default)]
+  favorite: bool,
+  // ordered like in the frontend
+  #[serde(default)]
+  fields: Vec<CustomField>,
+  // otpauth uri or base32 secret
+  #[serde(default)]
+  otp: Option<String>,
+  // previous passwords, newest first
+  #[serde(default)]
+  history: Vec<HistoryEntry>,
+  // unix timestamps in milliseconds, unknown for entries created before they were tracked
+  #[serde(default)]
+  created: Option<u64>,
+  #[serde(default)]
+  modified: Option<u64>,
+  #[serde(default)]
+  password_changed: Option<u64>,
+  #[serde(default)]
+  last_used: Option<u64>,
+  // unix timestamp in milliseconds, set while the entry is in the trash
+  #[serde(default)]
+  deleted: Option<u64>,
+  // the content is saved as separate blobs
+  #[serde(default)]
+  attachments: Vec<Attachment>,
+  // the type specific data
+  #[serde(default)]
+  item: Item,
   // identification
   uuid: String,
+  // base64 data key of the entry, saved wrapped next to the ciphertext
+  #[serde(skip)]
+  key: String,
+}
+
+impl Zeroize for PasswordData {
+  fn zeroize(&mut self) {
+    self.name.zeroize();
+    self.login.zeroize();
+    self.password.zeroize();
+    self.url.zeroize();
+    self.description.zeroize();
+    self.tags.zeroize();
+    self.fields.zeroize();
+    self.otp.zeroize();
+    self.history.zeroize();
+    self.item.zeroize();
+    self.attachments.zeroize();
+    self.key.zeroize();
+  }
+}
+
+// every clone handed to the frontend gets wiped as well
+impl Drop for PasswordData {
+  fn drop(&mut self) {
+    self.zeroize();
+  }
+}
+
+impl fmt::Debug for PasswordData {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("PasswordData")
+      .field("name", &self.name)
+      .field("login", &self.login.as_ref().map(|_| "<redacted>"))
+      .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+      .field("url", &self.url)
+      .field(
+        "description",
+        &self.description.as_ref().map(|_| "<redacted>"),
+      )
+      .field("folder", &self.folder)
+      .field("tags", &self.tags)
+      .field("favorite", &self.favorite)
+      .field("fields", &self.fields)
+      .field("otp", &self.otp.as_ref().map(|_| "<redacted>"))
+      .field("history", &self.history)
+      .field("created", &self.created)
+      .field("modified", &self.modified)
+      .field("password_changed", &self.password_changed)
+      .field("last_used", &self.last_used)
+      .field("deleted", &self.deleted)
+      .field("attachments", &self.attachments)
+      .field("item", &self.item)
+      .field("uuid", &self.uuid)
+      .field("key", &"<redacted>")
+      .finish()
+  }
+}
+
+impl PasswordData {
+  /// the secret which gets analysed, none for items without a human chosen secret
+  fn secret(&self) -> Option<&String> {
+    match self.item {
+      Item::Login => self.password.as_ref(),
+      _ => self.item.passphrase(),
+    }
+  }
+
+  /// the plaintext which may be searched, secrets and hidden fields are never included
+  pub fn search_terms(&self) -> Vec<&str> {
+    let mut terms = [&self.name, &self.login, &self.url, &self.description]
+      .iter()
+      .filter_map(|term| term.as_deref())
+      .collect::<Vec<&str>>();
+    terms.extend(self.item.notes().map(String::as_str));
+    terms.extend(self.tags.iter().map(String::as_str));
+    self.fields.iter().for_each(|field| {
+      terms.push(field.label());
+      terms.extend(field.searchable_value());
+    });
+
+    terms
+  }
+
+  /// hash of the values which identify an entry, used to skip duplicates on the import
+  fn duplicate_key(&self) -> u64 {
+    let place = self
+      .url
+      .as_deref()
+      .and_then(import::host)
+      .or_else(|| self.name.as_ref().map(|name| name.trim().to_lowercase()));
+    let item = Zeroizing::new(serde_json::to_string(&self.item).unwrap());
+
+    let mut hasher = DefaultHasher::new();
+    (place, &self.login, &self.password, item.as_str()).hash(&mut hasher);
+    hasher.finish()
+  }
+
+  /// the fields which can be listed without revealing any secret
+  fn summary(&self) -> PasswordSummary {
+    PasswordSummary {
+      uuid: self.uuid.clone(),
+      name: self.name.clone(),
+      login: self.login.clone(),
+      url: self.url.clone(),
+      folder: self.folder.clone(),
+      tags: self.tags.clone(),
+      favorite: self.favorite,
+      kind: self.item.kind(),
+      otp: self.otp.as_ref().is_some_and(|otp| !otp.is_empty()),
+      created: self.created,
+      modified: self.modified,
+      last_used: self.last_used,
+    }
+  }
+}
+
+/// search result, never contains the password or any other secret
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct PasswordSummary {
+  uuid: String,
+  name: Option<String>,
+  login: Option<String>,
+  url: Option<String>,
+  folder: Option<String>,
+  tags: Vec<String>,
+  favorite: bool,
+  kind: ItemKind,
+  // whether a code can be generated
+  otp: bool,
+  created: Option<u64>,
+  modified: Option<u64>,
+  last_used: Option<u64>,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -87,6 +344,68 @@ pub struct Password {
   iv: String,
   // encrypted, would be PasswordDat
   data: String,
+  // the data key wrapped by the file key, legacy entries are encrypted with the file key
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  key: Option<WrappedKey>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+struct WrappedKey {
+  iv: String,
+  key: String,
+}
+
+/// the stored form of an entry, the frontend never gets the keys of the attachments
+#[derive(Serialize)]
+struct SealedPassword<'a> {
+  #[serde(flatten)]
+  password: &'a PasswordData,
+  // base64 keys of the blobs by the id of the attachment
+  attachment_keys: BTreeMap<&'a str, &'a str>,
+}
+
+#[derive(Deserialize)]
+struct OpenedPassword {
+  #[serde(flatten)]
+  password: PasswordData,
+  // entries of older versions keep the keys inside of the attachments
+  #[serde(default)]
+  attachment_keys: HashMap<String, String>,
+}
+
+impl<'a> From<&'a PasswordData> for SealedPassword<'a> {
+  fn from(password: &'a PasswordData) -> Self {
+    Self {
+      password,
+      attachment_keys: password
+        .attachments
+        .iter()
+        .map(|attachment| (attachment.id(), attachment.key()))
+        .collect(),
+    }
+  }
+}
+
+impl OpenedPassword {
+  /// move the keys back into the attachments
+  fn into_password(mut self) -> PasswordData {
+    let keys = &mut self.attachment_keys;
+    self.password.attachments.iter_mut().for_each(|attachment| {
+      if let Some(key) = keys.remove(attachment.id()) {
+        attachment.set_key(key);
+      }
+    });
+    keys.values_mut().for_each(|key| key.zeroize());
+    self.password
+  }
+}
+
+/// stored password which could not be decrypted or parsed
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct CorruptEntry {
+  // the unique iv of the entry
+  id: String,
+  reason: String,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -96,6 +415,15 @@ pub enum PasswordType {
   Raw(Password),
 }
 
+impl Zeroize for PasswordType {
+  fn zeroize(&mut self) {
+    // raw entries are still encrypted
+    if let PasswordType::Data(data) = self {
+      data.zeroize();
+    }
+  }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct User {
   // identification for login
@@ -111,6 +439,32 @@ pub struct User {
   passwords: Vec<PasswordType>,
   // the default generator for the user
   generator: PasswordGenerator,
+  // encrypted like the passwords
+  folders: Vec<Folder>,
+  // how many previous passwords are kept per entry
+  history_retention: usize,
+  // days until deleted passwords are purged, 0 keeps them forever
+  trash_retention: u64,
+  // days until a password is seen as old, 0 disables the check
+  password_max_age: u64,
+  // when the unlocked vault gets locked again
+  auto_lock: AutoLock,
+  // name of the vault file, the lowercase username or an opaque id for private vaults
+  file: String,
+  // whether the metadata is encrypted and the file is listed in the index
+  private: bool,
+  // held until the user gets dropped
+  #[serde(skip_serializing, skip_deserializing)]
+  _lock: Option<VaultLock>,
+  // hash of the file content after the last read or write
+  #[serde(skip_serializing, skip_deserializing)]
+  fingerprint: Cell<Option<u64>>,
+  // the entries which failed on the login, kept as `PasswordType::Raw`
+  #[serde(skip_serializing, skip_deserializing)]
+  corrupt: Vec<CorruptEntry>,
+  // blobs which are no longer referenced, removed after the next write
+  #[serde(skip_serializing, skip_deserializing)]
+  orphans: RefCell<Vec<String>>,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
@@ -123,77 +477,403 @@ struct RawUserPassword {
   hash: String,
   // the original salt
   salt: String,
+  // parameters of the password hashing
+  kdf: Kdf,
+}
+
+impl RawUserPassword {
+  /// wrap the file key with the hash of the password
+  fn new(password: &[u8], key: &str, kdf: Kdf) -> Result<Self, ConfigError> {
+    // hash the password
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = kdf.hash(password, &salt)?;
+
+    // encrypt the key for storage
+    let key_encryption = Encryption::new(hash.key.as_ref());
+    let key = key_encryption.encrypt(key)?;
+
+    // second time because we encrypt the key with the first hash
+    let hash_salt = SaltString::generate(&mut OsRng);
+    let hash = Pbkdf2.hash_password(hash.encoded.as_bytes(), &hash_salt)?;
+
+    Ok(Self {
+      key: key.ciphertext,
+      iv: key.nonce,
+      hash: hash.to_string(),
+      salt: salt.as_str().to_string(),
+      kdf,
+    })
+  }
+
+  /// check whether the key should be wrapped again with the current formats
+  fn outdated(&self) -> bool {
+    self.kdf.outdated() || Encryption::is_legacy(self.iv.as_str())
+  }
+
+  /// verify the password and decrypt the file key
+  fn unlock(&self, password: &[u8]) -> Result<Zeroizing<String>, ConfigError> {
+    // hash the input password
+    let salt = SaltString::new(self.salt.as_str())?;
+    let hash = self.kdf.hash(password, &salt)?;
+
+    // match the hashes
+    let stored = PasswordHash::new(self.hash.as_str())?;
+    if Pbkdf2
+      .verify_password(hash.encoded.as_bytes(), &stored)
+      .is_err()
+    {
+      return Err(ConfigError::Unknown);
+    }
+
+    // decrypt the stored key
+    let encryption = Encryption::new(hash.key.as_ref());
+    Ok(Zeroizing::new(
+      encryption.decrypt(self.key.clone(), self.iv.clone())?,
+    ))
+  }
+}
+
+/// hash of the file content to detect changes of other sessions
+fn fingerprint(content: &[u8]) -> u64 {
+  let mut hasher = DefaultHasher::new();
+  content.hash(&mut hasher);
+  hasher.finish()
+}
+
+/// current version of the vault file
+///
+/// bumped with every change of the stored data, the encrypted entries included, so older builds
+/// refuse newer vaults instead of dropping the fields they do not know
+const FORMAT_VERSION: u64 = 12;
+
+/// days until deleted passwords are purged by default
+const DEFAULT_TRASH_RETENTION: u64 = 30;
+
+fn default_trash_retention() -> u64 {
+  DEFAULT_TRASH_RETENTION
+}
+
+/// days until a password is seen as old by default
+const DEFAULT_PASSWORD_MAX_AGE: u64 = 365;
+
+fn default_password_max_age() -> u64 {
+  DEFAULT_PASSWORD_MAX_AGE
+}
+
+/// the payload of private vaults is padded to a multiple of this size
+const PRIVATE_PADDING: usize = 4096;
+
+/// single upgrade step of the vault file
+type Migration = fn(&mut Value) -> Result<(), ConfigError>;
+
+/// the migrations indexed by the version they upgrade from
+const MIGRATIONS: [Migration; FORMAT_VERSION as usize] = [
+  migrate_v0,
+  migrate_v1,
+  migrate_v2,
+  migrate_v3,
+  migrate_v4,
+  migrate_v5,
+  migrate_v6,
+  migrate_v7,
+  migrate_v8,
+  migrate_v9,
+  migrate_v10,
+  migrate_v11,
+];
+
+/// v0 -> v1: kdf descriptor and the sync credentials of the backup
+fn migrate_v0(raw: &mut Value) -> Result<(), ConfigError> {
+  // the password has been hashed with the pbkdf2 defaults
+  let password = raw["password"]
+    .as_object_mut()
+    .ok_or(ConfigError::FormatError)?;
+  password.insert("kdf".to_string(), serde_json::to_value(Kdf::legacy())?);
+
+  // the backup has not been able to sync yet
+  if let Some(backup) = raw["backup"].as_object_mut() {
+    for field in ["url", "password", "password_iv"] {
+      backup.insert(field.to_string(), Value::from(""));
+    }
+  }
+  Ok(())
+}
+
+/// v1 -> v2: auto lock settings
+fn migrate_v1(raw: &mut Value) -> Result<(), ConfigError> {
+  insert_default(raw, "auto_lock", serde_json::to_value(AutoLock::default())?)
+}
+
+/// v2 -> v3: folders of the passwords
+fn migrate_v2(raw: &mut Value) -> Result<(), ConfigError> {
+  insert_default(raw, "folders", Value::from(Vec::<Value>::new()))
+}
+
+/// v3 -> v4: item kinds of the entries
+fn migrate_v3(_raw: &mut Value) -> Result<(), ConfigError> {
+  // the item of the entries defaults to a login on the decryption
+  Ok(())
+}
+
+/// v4 -> v5: custom fields of the entries
+fn migrate_v4(_raw: &mut Value) -> Result<(), ConfigError> {
+  // the fields of the entries default to an empty list on the decryption
+  Ok(())
+}
+
+/// v5 -> v6: one time passwords of the entries
+fn migrate_v5(_raw: &mut Value) -> Result<(), ConfigError> {
+  // the otp of the entries defaults to none on the decryption
+  Ok(())
+}
+
+/// v6 -> v7: history retention and the password history of the entries
+fn migrate_v6(raw: &mut Value) -> Result<(), ConfigError> {
+  insert_default(
+    raw,
+    "history_retention",
+    Value::from(history::DEFAULT_RETENTION),
+  )
+}
+
+/// v7 -> v8: trash retention and the deletion of the entries
+fn migrate_v7(raw: &mut Value) -> Result<(), ConfigError> {
+  insert_default(raw, "trash_retention", Value::from(DEFAULT_TRASH_RETENTION))
+}
+
+/// v8 -> v9: password max age and the timestamps of the entries
+fn migrate_v8(raw: &mut Value) -> Result<(), ConfigError> {
+  insert_default(
+    raw,
+    "password_max_age",
+    Value::from(DEFAULT_PASSWORD_MAX_AGE),
+  )
+}
+
+/// v9 -> v10: attachments of the entries
+fn migrate_v9(_raw: &mut Value) -> Result<(), ConfigError> {
+  // the attachments of the entries default to an empty list on the decryption
+  Ok(())
+}
+
+/// v10 -> v11: data keys of the entries
+fn migrate_v10(_raw: &mut Value) -> Result<(), ConfigError> {
+  // entries without a wrapped data key are encrypted with the file key and get one on the write
+  Ok(())
+}
+
+/// v11 -> v12: private layout
+fn migrate_v11(_raw: &mut Value) -> Result<(), ConfigError> {
+  // plain vaults have no private payload
+  Ok(())
+}
+
+/// add a field which is missing in the files of older versions
+fn insert_default(raw: &mut Value, field: &str, value: Value) -> Result<(), ConfigError> {
+  raw
+    .as_object_mut()
+    .ok_or(ConfigError::FormatError)?
+    .entry(field)
+    .or_insert(value);
+  Ok(())
 }
 
 #[derive(Deserialize, Serialize)]
 pub struct RawUser {
+  // files without version are v0
+  format_version: u64,
   username: String,
   backup: Option<Backup>,
   password: RawUserPassword,
   // base64 encoded and encrypted
   passwords: Vec<Password>,
   generator: PasswordGenerator,
+  // base64 encoded and encrypted
+  #[serde(default)]
+  folders: Vec<Password>,
+  #[serde(default = "history::default_retention")]
+  history_retention: usize,
+  #[serde(default = "default_trash_retention")]
+  trash_retention: u64,
+  #[serde(default = "default_password_max_age")]
+  password_max_age: u64,
+  #[serde(default)]
+  auto_lock: AutoLock,
+  // the encrypted content of private vaults, the fields above keep their defaults
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  private: Option<CipherText>,
 }
 
 impl RawUser {
   /// create an new user just by the username and the disk
   /// does not not contain any usable sensitive data
-  fn new_from_disk(directory: &PathBuf, username: &str) -> Result<Self, ConfigError> {
+  /// returns the fingerprint of the file too
+  fn new_from_disk(directory: &PathBuf, file: &str) -> Result<(Self, u64), ConfigError> {
     // create path for the possible user
-    let path = directory
-      .join(username.to_lowercase().as_str())
-      .with_extension("json");
+    let path = directory.join(file).with_extension("json");
     // read file content
     let content = fs::read_to_string(path)?;
 
-    // parse json
-    let parsed = serde_json::from_str::<Self>(content.as_str())?;
-    Ok(parsed)
+    Ok((
+      Self::parse(content.as_str())?,
+      fingerprint(content.as_bytes()),
+    ))
+  }
+
+  /// parse the json of any known version
+  fn parse(content: &str) -> Result<Self, ConfigError> {
+    let mut raw = serde_json::from_str::<Value>(content)?;
+    if !raw.is_object() {
+      return Err(ConfigError::FormatError);
+    }
+
+    // get the version of the file
+    let version = match raw.get("format_version") {
+      Some(version) => version.as_u64().ok_or(ConfigError::FormatError)?,
+      None => 0,
+    };
+    // files of newer app versions can not be read
+    if version > FORMAT_VERSION {
+      return Err(ConfigError::VersionError(version));
+    }
+
+    // upgrade step by step
+    for migration in &MIGRATIONS[version as usize..] {
+      migration(&mut raw)?;
+    }
+    raw["format_version"] = Value::from(FORMAT_VERSION);
+
+    Ok(serde_json::from_value(raw)?)
   }
 
-  fn write_to_disk(&self, path: &PathBuf) -> Result<(), ConfigError> {
+  /// move everything but the wrapped key into the encrypted payload
+  fn seal(self, encryption: &Encryption) -> Self {
+    let mut content = serde_json::to_string(&self).unwrap();
+    // pad, so the size only reveals the magnitude of the vault
+    let padded = (content.len() / PRIVATE_PADDING + 1) * PRIVATE_PADDING;
+    content.push_str(" ".repeat(padded - content.len()).as_str());
+    let content = Zeroizing::new(content);
+
+    Self {
+      format_version: FORMAT_VERSION,
+      username: String::new(),
+      backup: None,
+      password: self.password.clone(),
+      passwords: Vec::new(),
+      generator: PasswordGenerator::default(),
+      folders: Vec::new(),
+      history_retention: history::DEFAULT_RETENTION,
+      trash_retention: DEFAULT_TRASH_RETENTION,
+      password_max_age: DEFAULT_PASSWORD_MAX_AGE,
+      auto_lock: AutoLock::default(),
+      private: Some(encryption.encrypt(content.as_str()).unwrap()),
+    }
+  }
+
+  /// decrypt the payload of private vaults
+  fn unseal(self, encryption: &Encryption) -> Result<Self, ConfigError> {
+    match self.private {
+      Some(private) => {
+        let content = Zeroizing::new(encryption.decrypt(private.ciphertext, private.nonce)?);
+        Self::parse(content.as_str())
+      }
+      None => Ok(self),
+    }
+  }
+
+  /// returns the fingerprint of the written content
+  fn write_to_disk(&self, path: &PathBuf) -> Result<u64, ConfigError> {
     // stringify
     let raw = serde_json::to_string(&self).unwrap();
-    // write the bytes into the file
-    fs::write(&path, raw.as_bytes())?;
-    Ok(())
+    // write into a temporary file in the same directory, so the rename can not cross filesystems
+    let mut file = NamedTempFile::new_in(path.parent().unwrap())?;
+    file.write_all(raw.as_bytes())?;
+    // the data has to be on the disk before the old file gets replaced
+    file.as_file().sync_all()?;
+    // atomically replace the old file
+    file.persist(&path).map_err(|error| error.error)?;
+    // persist the rename itself
+    #[cfg(unix)]
+    fs::File::open(path.parent().unwrap())?.sync_all()?;
+    Ok(fingerprint(raw.as_bytes()))
   }
 }
 
 impl From<&User> for RawUser {
   fn from(user: &User) -> Self {
+    let encryption = user.encryption.clone().unwrap();
     // encrypt the passwords
     let passwords = user
       .passwords
       .iter()
-      .map(|password| {
-        // stringify
-        let raw = serde_json::to_string(password).unwrap();
-        // encrypt
-        let encrypted = user
-          .encryption
-          .clone()
-          .unwrap()
-          .encrypt(raw.as_str())
-          .unwrap();
-
-        Password {
-          iv: encrypted.nonce,
-          data: encrypted.ciphertext,
-        }
+      .map(|password| match password {
+        PasswordType::Data(password) => seal_password(password, &encryption),
+        // keep broken entries untouched
+        PasswordType::Raw(password) => password.clone(),
       })
       .collect::<Vec<Password>>();
+    let folders = user
+      .folders
+      .iter()
+      .map(|folder| encrypt_entry(folder, &encryption))
+      .collect::<Vec<Password>>();
 
-    Self {
+    // the backup credentials are only in plaintext in memory
+    let backup = user
+      .backup
+      .as_ref()
+      .map(|backup| backup.encrypt(&encryption).unwrap());
+
+    let raw = Self {
+      format_version: FORMAT_VERSION,
       username: user.username(),
-      backup: user.backup(),
+      backup,
       password: user.password.clone(),
       passwords,
       generator: user.generator(),
+      folders,
+      history_retention: user.history_retention,
+      trash_retention: user.trash_retention,
+      password_max_age: user.password_max_age,
+      auto_lock: user.auto_lock(),
+      private: None,
+    };
+
+    match user.private {
+      true => raw.seal(&encryption),
+      false => raw,
     }
   }
 }
 
+/// stringify and encrypt a single entry
+fn encrypt_entry<T: Serialize>(entry: &T, encryption: &Encryption) -> Password {
+  // stringify
+  let raw = Zeroizing::new(serde_json::to_string(entry).unwrap());
+  // encrypt
+  let encrypted = encryption.encrypt(raw.as_str()).unwrap();
+
+  Password {
+    iv: encrypted.nonce,
+    data: encrypted.ciphertext,
+    key: None,
+  }
+}
+
+/// encrypt a password with its own data key and wrap the key with the file key
+fn seal_password(password: &PasswordData, encryption: &Encryption) -> Password {
+  let key = Encryption::from_base64(password.key.as_str()).unwrap();
+  let wrapped = encryption.encrypt(password.key.as_str()).unwrap();
+
+  Password {
+    key: Some(WrappedKey {
+      iv: wrapped.nonce,
+      key: wrapped.ciphertext,
+    }),
+    ..encrypt_entry(&SealedPassword::from(password), &key)
+  }
+}
+
 impl User {
   /// get the username
   pub fn username(&self) -> String {
@@ -210,52 +890,63 @@ impl User {
     self.generator = generator
   }
 
+  /// get the auto lock settings
+  pub fn auto_lock(&self) -> AutoLock {
+    self.auto_lock.clone()
+  }
+
+  /// update the auto lock settings
+  pub fn update_auto_lock(&mut self, auto_lock: AutoLock) {
+    self.auto_lock = auto_lock
+  }
+
   /// create new user from signup information
   pub fn new_from_signup(directory: &PathBuf, data: UserData) -> Result<Self, ConfigError> {
     // create the path
-    let path = directory
-      .join(&data.username.to_lowercase())
-      .with_extension("json");
+    let file = data.username.to_lowercase();
+    let path = directory.join(&file).with_extension("json");
     // check for already existing user
-    match &path.exists() {
+    let exists = match Self::locate(directory, data.username.as_str()) {
+      Ok(_) => true,
+      Err(ConfigError::IOError(error)) if error.kind() == ErrorKind::NotFound => false,
+      Err(error) => return Err(error),
+    };
+    match exists {
       // return err on true, because we will not overwrite any userdata
-      true => return Err(ConfigError::Unknown),
+      true => return Err(ConfigError::UserExistsError),
       false => {
-        // hash the password
-        let salt = SaltString::generate(&mut OsRng);
-        let hash_salt = salt.as_str();
-        let hash = Pbkdf2
-          .hash_password(data.password.as_bytes(), &salt)
-          .unwrap();
-
         // setup the file key as random base64
-        let key = Encryption::generate(32);
+        let key = Zeroizing::new(Encryption::generate(32));
         // build the initial encryption
-        let encryption = Encryption::new(base64::decode(&key).unwrap().as_slice());
+        let encryption = Encryption::from_base64(key.as_str())?;
 
         // encrypt it for storage
-        let key_encryption = Encryption::new(hash.hash.unwrap().as_bytes());
-        let key = key_encryption.encrypt(key.as_str())?;
+        let password =
+          RawUserPassword::new(data.password.as_bytes(), key.as_str(), Kdf::default())?;
 
-        // second time because we encrypt the key with the first hash
-        let salt = SaltString::generate(&mut OsRng);
-        let hash = Pbkdf2
-          .hash_password(hash.to_string().as_bytes(), &salt)
-          .unwrap();
+        // lock the new vault before the first write
+        let lock =
+          VaultLock::acquire(directory, data.username.as_str())?.ok_or(ConfigError::LockedError)?;
 
         // init the user
         let user = Self {
-          username: data.username,
+          username: data.username.clone(),
           encryption: Some(encryption.clone()),
           backup: None,
-          password: RawUserPassword {
-            key: key.ciphertext,
-            iv: key.nonce,
-            hash: hash.to_string(),
-            salt: hash_salt.to_string(),
-          },
+          password,
           passwords: Vec::new(),
           generator: PasswordGenerator::default(),
+          folders: Vec::new(),
+          history_retention: history::DEFAULT_RETENTION,
+          trash_retention: DEFAULT_TRASH_RETENTION,
+          password_max_age: DEFAULT_PASSWORD_MAX_AGE,
+          auto_lock: AutoLock::default(),
+          file,
+          private: false,
+          _lock: Some(lock),
+          fingerprint: Cell::new(None),
+          corrupt: Vec::new(),
+          orphans: RefCell::new(Vec::new()),
         };
 
         // save the data
@@ -270,120 +961,1256 @@ impl User {
   /// init new full user based on login credentials
   pub fn new_from_login(directory: &PathBuf, data: UserData) -> Result<Self, ConfigError> {
     // load raw user
-    let mut raw = RawUser::new_from_disk(directory, data.username.to_lowercase().as_str())?;
-    let cloned = raw.password.clone();
+    let file = Self::locate(directory, data.username.as_str())?;
+    // only a single session per vault
+    let lock = VaultLock::acquire(directory, file.as_str())?.ok_or(ConfigError::LockedError)?;
+    let (raw, fingerprint) = RawUser::new_from_disk(directory, file.as_str())?;
+    // verify the password and decrypt the stored key
+    let key = raw.password.unlock(data.password.as_bytes())?;
 
-    // compare passwords
-    let hash = PasswordHash::new(cloned.hash.as_str()).unwrap();
-    // hash the input password
-    let salt = SaltString::new(raw.password.salt.as_str()).unwrap();
-    let password = Pbkdf2
-      .hash_password(data.password.as_bytes(), &salt)
-      .unwrap();
-    // match the hashes
-    match Pbkdf2.verify_password(password.to_string().as_bytes(), &hash) {
-      Ok(()) => {
-        // decrypt the stored key
-        let encryption = Encryption::new(password.hash.unwrap().as_bytes());
-        let key = encryption.decrypt(cloned.key, cloned.iv)?;
-
-        // create new encryption for the user
-        let encryption = Encryption::new(base64::decode(key).unwrap().as_slice());
-
-        // init backup
-        if let Some(backup) = raw.backup {
-          raw.backup = Some(backup.init_from_login(&encryption).unwrap());
-        }
+    // create new encryption for the user
+    let encryption = Encryption::from_base64(key.as_str())?;
+    let private = raw.private.is_some();
+    let mut raw = raw.unseal(&encryption)?;
 
-        // decrypt the passwords
-        let passwords = raw
-          .passwords
-          .iter()
-          .map(|password| {
-            // decrypt the data
-            let raw = encryption
-              .decrypt(password.data.clone(), password.iv.clone())
-              .unwrap();
-            // parse json
-            PasswordType::Data(serde_json::from_str::<PasswordData>(raw.as_str()).unwrap())
-          })
-          .collect::<Vec<PasswordType>>();
-
-        Ok(Self {
-          username: raw.username,
-          backup: raw.backup,
-          encryption: Some(encryption),
-          password: raw.password,
-          passwords,
-          generator: raw.generator,
-        })
-      }
-      Err(_) => Err(ConfigError::Unknown),
+    // rewrap the key of old vaults with the current kdf and nonce format
+    let outdated = raw.password.outdated();
+    if outdated {
+      raw.password = RawUserPassword::new(data.password.as_bytes(), key.as_str(), Kdf::default())?;
+    }
+
+    // init backup
+    if let Some(backup) = raw.backup {
+      raw.backup = Some(backup.init_from_login(&encryption)?);
+    }
+
+    // decrypt the passwords, broken entries do not prevent the login
+    let (passwords, corrupt) = Self::decrypt_passwords_lenient(&raw.passwords, &encryption);
+    let folders = Self::decrypt_folders(&raw.folders, &encryption)?;
+
+    let mut user = Self {
+      username: raw.username,
+      backup: raw.backup,
+      encryption: Some(encryption),
+      password: raw.password,
+      passwords,
+      generator: raw.generator,
+      folders,
+      history_retention: raw.history_retention,
+      trash_retention: raw.trash_retention,
+      password_max_age: raw.password_max_age,
+      auto_lock: raw.auto_lock,
+      file,
+      private,
+      _lock: Some(lock),
+      fingerprint: Cell::new(Some(fingerprint)),
+      corrupt,
+      orphans: RefCell::new(Vec::new()),
+    };
+
+    // blobs of interrupted writes, removed with the next write unless a snapshot needs them
+    if user.corrupt.is_empty() {
+      let referenced = Self::attachment_ids(&user.passwords);
+      let blobs = Attachments::new(directory, user.file.as_str()).list()?;
+      user
+        .orphans
+        .borrow_mut()
+        .extend(blobs.into_iter().filter(|id| !referenced.contains(id)));
+    }
+
+    // remove old passwords from the trash
+    let purged = user.purge_expired_trash(util::now());
+
+    // save the upgraded key
+    if outdated || purged > 0 {
+      user.write(directory)?;
     }
+
+    Ok(user)
   }
 
-  /// write the userdata into the file
-  pub fn write(&self, path: &PathBuf) -> Result<(), ConfigError> {
-    // create the raw data
-    let raw = RawUser::from(self);
-    // write the data
-    raw.write_to_disk(
-      &path
-        // name equals lowercase name
-        .join(&self.username.to_lowercase())
-        // format is json
-        .with_extension("json"),
-    )
+  /// check whether the credentials belong to this vault
+  pub fn verify_login(&self, data: &UserData) -> bool {
+    self.username.to_lowercase() == data.username.to_lowercase()
+      && self.password.unlock(data.password.as_bytes()).is_ok()
   }
 
-  /// create new password
-  pub fn new_password(&mut self) -> PasswordData {
-    let data = PasswordData {
-      login: None,
-      password: None,
-      url: None,
-      description: None,
-      uuid: Uuid::new_v4().to_string(),
-      name: Some("Unnamed".to_string()),
-    };
+  /// find the file of a vault, private vaults are looked up in the index
+  fn locate(directory: &PathBuf, username: &str) -> Result<String, ConfigError> {
+    let file = username.to_lowercase();
+    if directory.join(&file).with_extension("json").exists() {
+      return Ok(file);
+    }
 
-    // push the new password
-    self.passwords.push(PasswordType::Data(data.clone()));
-    data
+    match VaultIndex::find(directory, username)? {
+      Some(file) => Ok(file),
+      // the same error as without the index
+      None => Err(io::Error::new(ErrorKind::NotFound, "Unknown user").into()),
+    }
   }
 
-  /// update an specific password
-  pub fn update_password(&mut self, data: PasswordData) {
-    // update an existing password
-    self.passwords = self
-      .passwords
-      .clone()
-      .into_iter()
-      .map(|ty| {
-        if let PasswordType::Data(password) = ty.clone() {
-          if password.clone().uuid.eq(&data.uuid) {
-            return PasswordType::Data(data.clone());
-          }
-        }
-        ty
+  /// decrypt and parse a single stored entry
+  fn decrypt_entry<T: DeserializeOwned>(
+    password: &Password,
+    encryption: &Encryption,
+  ) -> Result<T, ConfigError> {
+    // decrypt the data
+    let raw = Zeroizing::new(encryption.decrypt(password.data.clone(), password.iv.clone())?);
+    // parse json
+    Ok(serde_json::from_str::<T>(raw.as_str())?)
+  }
+
+  /// unwrap the data key and decrypt a single stored password
+  fn open_password(
+    password: &Password,
+    encryption: &Encryption,
+  ) -> Result<PasswordData, ConfigError> {
+    match &password.key {
+      Some(wrapped) => {
+        let key = Zeroizing::new(encryption.decrypt(wrapped.key.clone(), wrapped.iv.clone())?);
+        let mut data =
+          Self::decrypt_entry::<OpenedPassword>(password, &Encryption::from_base64(key.as_str())?)?
+            .into_password();
+        data.key = key.to_string();
+        Ok(data)
+      }
+      // legacy entries get their own key with the next write
+      None => {
+        let mut data = Self::decrypt_entry::<OpenedPassword>(password, encryption)?.into_password();
+        data.key = Encryption::generate(32);
+        Ok(data)
+      }
+    }
+  }
+
+  /// decrypt and parse the stored folders
+  fn decrypt_folders(
+    folders: &[Password],
+    encryption: &Encryption,
+  ) -> Result<Vec<Folder>, ConfigError> {
+    folders
+      .iter()
+      .map(|folder| Self::decrypt_entry(folder, encryption))
+      .collect()
+  }
+
+  /// decrypt and parse the stored passwords, fails on the first broken entry
+  fn decrypt_passwords(
+    passwords: &[Password],
+    encryption: &Encryption,
+  ) -> Result<Vec<PasswordType>, ConfigError> {
+    passwords
+      .iter()
+      .map(|password| {
+        Ok(PasswordType::Data(Self::open_password(
+          password, encryption,
+        )?))
       })
-      .collect::<Vec<PasswordType>>();
+      .collect()
   }
 
-  /// delete an existing password
-  pub fn delete_password(&mut self, data: PasswordData) {
-    // remove it via filter
-    self.passwords = self
-      .passwords
-      .clone()
-      .into_iter()
-      .filter(|ty| {
-        if let PasswordType::Data(password) = ty.clone() {
-          return !password.uuid.eq(&data.uuid);
+  /// decrypt and parse the stored passwords, broken entries are kept encrypted and reported
+  fn decrypt_passwords_lenient(
+    passwords: &[Password],
+    encryption: &Encryption,
+  ) -> (Vec<PasswordType>, Vec<CorruptEntry>) {
+    let mut corrupt = Vec::new();
+    let passwords = passwords
+      .iter()
+      .map(|password| match Self::open_password(password, encryption) {
+        Ok(data) => PasswordType::Data(data),
+        Err(error) => {
+          corrupt.push(CorruptEntry {
+            id: password.iv.clone(),
+            reason: error.to_string(),
+          });
+          PasswordType::Raw(password.clone())
         }
-        true
       })
-      .collect::<Vec<PasswordType>>();
+      .collect();
+
+    (passwords, corrupt)
+  }
+
+  /// the entries which could not be read on the login
+  pub fn corrupt_entries(&self) -> Vec<CorruptEntry> {
+    self.corrupt.clone()
+  }
+
+  /// the still encrypted data of the broken entries
+  pub fn export_corrupt_entries(&self) -> Vec<Password> {
+    self
+      .passwords
+      .iter()
+      .filter_map(|ty| match ty {
+        PasswordType::Raw(password) => Some(password.clone()),
+        PasswordType::Data(_) => None,
+      })
+      .collect()
+  }
+
+  /// remove a broken entry
+  pub fn delete_corrupt_entry(&mut self, id: &str) {
+    self.passwords.retain(|ty| match ty {
+      PasswordType::Raw(password) => !password.iv.eq(id),
+      PasswordType::Data(_) => true,
+    });
+    self.corrupt.retain(|entry| !entry.id.eq(id));
+  }
+
+  /// write the userdata into the file
+  pub fn write(&self, path: &PathBuf) -> Result<(), ConfigError> {
+    // create the raw data
+    let raw = RawUser::from(self);
+    let file = path
+      // name equals lowercase name or the opaque id
+      .join(&self.file)
+      // format is json
+      .with_extension("json");
+
+    // refuse to overwrite the changes of another session
+    self.check_conflict(&file)?;
+    // keep the previous state
+    Snapshots::new(path, self.file.as_str()).create(&file)?;
+    // write the data
+    let fingerprint = raw.write_to_disk(&file)?;
+    self.fingerprint.set(Some(fingerprint));
+
+    self.remove_orphans(path);
+    Ok(())
+  }
+
+  /// apply a change and write it, the change is undone if it could not be saved
+  pub fn apply<T>(
+    &mut self,
+    path: &PathBuf,
+    change: impl FnOnce(&mut Self) -> Result<T, ConfigError>,
+  ) -> Result<T, ConfigError> {
+    let passwords = self.passwords.clone();
+    let folders = self.folders.clone();
+    let corrupt = self.corrupt.clone();
+    let backup = self.backup.clone();
+    let generator = self.generator.clone();
+    let auto_lock = self.auto_lock.clone();
+    let settings = (
+      self.history_retention,
+      self.trash_retention,
+      self.password_max_age,
+    );
+
+    let result = change(self).and_then(|value| self.write(path).map(|_| value));
+    if result.is_err() {
+      // blobs written by the change become orphans
+      self.replace_passwords(passwords);
+      self.folders = folders;
+      self.corrupt = corrupt;
+      self.backup = backup;
+      self.generator = generator;
+      self.auto_lock = auto_lock;
+      let (history_retention, trash_retention, password_max_age) = settings;
+      self.history_retention = history_retention;
+      self.trash_retention = trash_retention;
+      self.password_max_age = password_max_age;
+    }
+    result
+  }
+
+  /// remove the blobs the vault does not reference anymore, unless a snapshot still does
+  fn remove_orphans(&self, directory: &PathBuf) {
+    if self.orphans.borrow().is_empty() {
+      return;
+    }
+
+    let current = Self::attachment_ids(&self.passwords);
+    // unreadable snapshots keep all blobs
+    if let Ok(referenced) = self.snapshot_attachment_ids(directory) {
+      let attachments = Attachments::new(directory, self.file.as_str());
+      // blobs of snapshots and failed removals are retried later
+      self.orphans.borrow_mut().retain(|id| {
+        !current.contains(id) && (referenced.contains(id) || attachments.remove(id).is_err())
+      });
+    }
+  }
+
+  /// ids of the blobs referenced by the passwords
+  fn attachment_ids(passwords: &[PasswordType]) -> HashSet<String> {
+    passwords
+      .iter()
+      .filter_map(|ty| match ty {
+        PasswordType::Data(password) => Some(password),
+        PasswordType::Raw(_) => None,
+      })
+      .flat_map(|password| {
+        password
+          .attachments
+          .iter()
+          .map(|attachment| attachment.id().to_string())
+      })
+      .collect()
+  }
+
+  /// ids of the blobs referenced by any snapshot
+  fn snapshot_attachment_ids(&self, directory: &PathBuf) -> Result<HashSet<String>, ConfigError> {
+    let snapshots = Snapshots::new(directory, self.file.as_str());
+    let encryption = self.encryption.as_ref().unwrap();
+
+    let mut ids = HashSet::new();
+    for snapshot in snapshots.list()? {
+      let content = snapshots.read(snapshot.id().as_str())?;
+      let raw = RawUser::parse(content.as_str())?.unseal(encryption)?;
+      ids.extend(Self::attachment_ids(&Self::decrypt_passwords(
+        &raw.passwords,
+        encryption,
+      )?));
+    }
+    Ok(ids)
+  }
+
+  /// replace the passwords, the blobs only referenced by the previous ones become orphans
+  fn replace_passwords(&mut self, passwords: Vec<PasswordType>) {
+    let previous = Self::attachment_ids(&self.passwords);
+    self.passwords = passwords;
+    let current = Self::attachment_ids(&self.passwords);
+    self
+      .orphans
+      .borrow_mut()
+      .extend(previous.difference(&current).cloned());
+  }
+
+  /// compare the file on the disk with the last known state
+  fn check_conflict(&self, file: &PathBuf) -> Result<(), ConfigError> {
+    let current = match fs::read(file) {
+      Ok(content) => Some(fingerprint(content.as_slice())),
+      Err(error) if error.kind() == ErrorKind::NotFound => None,
+      Err(error) => return Err(error.into()),
+    };
+
+    match self.fingerprint.get() {
+      // the file has never been written by this session
+      None => Ok(()),
+      Some(expected) if current == Some(expected) => Ok(()),
+      Some(_) => Err(ConfigError::ConflictError),
+    }
+  }
+
+  /// list the snapshots of the vault, newest first
+  pub fn list_snapshots(&self, directory: &Path) -> Result<Vec<Snapshot>, ConfigError> {
+    Ok(Snapshots::new(directory, self.file.as_str()).list()?)
+  }
+
+  /// roll back to the state of a snapshot
+  ///
+  /// the master password stays untouched, the snapshots are removed on a key rotation
+  pub fn restore_snapshot(&mut self, directory: &PathBuf, id: &str) -> Result<(), ConfigError> {
+    let content = Snapshots::new(directory, self.file.as_str()).read(id)?;
+
+    // decrypt with the current key
+    let encryption = self.encryption.as_ref().unwrap();
+    let raw = RawUser::parse(content.as_str())?.unseal(encryption)?;
+    let passwords = Self::decrypt_passwords(&raw.passwords, encryption)?;
+    let folders = Self::decrypt_folders(&raw.folders, encryption)?;
+    let backup = match raw.backup {
+      Some(backup) => Some(backup.init_from_login(encryption)?),
+      None => None,
+    };
+
+    // the current state becomes a snapshot itself
+    let RawUser {
+      generator,
+      auto_lock,
+      history_retention,
+      trash_retention,
+      password_max_age,
+      ..
+    } = raw;
+    self.apply(directory, |user| {
+      user.replace_passwords(passwords);
+      user.folders = folders;
+      user.backup = backup;
+      user.generator = generator;
+      user.auto_lock = auto_lock;
+      user.history_retention = history_retention;
+      user.trash_retention = trash_retention;
+      user.password_max_age = password_max_age;
+      Ok(())
+    })
+  }
+
+  /// change the master password
+  ///
+  /// only the file key gets wrapped again, the passwords are still encrypted with the same key
+  pub fn change_password(
+    &mut self,
+    directory: &PathBuf,
+    current: &str,
+    password: &str,
+  ) -> Result<(), ConfigError> {
+    // verify the current password
+    let key = self.password.unlock(current.as_bytes())?;
+    // wrap the key with a new salt
+    let wrapped =
+      RawUserPassword::new(password.as_bytes(), key.as_str(), self.password.kdf.clone())?;
+
+    let previous = std::mem::replace(&mut self.password, wrapped);
+    // keep the old password if the new one could not be saved
+    if let Err(error) = self.write(directory) {
+      self.password = previous;
+      return Err(error);
+    }
+    Ok(())
+  }
+
+  /// replace the file key and encrypt everything again
+  ///
+  /// every entry and attachment gets a new key as well. the key and all the passwords are saved
+  /// with a single atomic write and the blobs are encrypted again under new ids, so an
+  /// interruption leaves either the old or the new vault on the disk
+  pub fn rotate_vault_key(
+    &mut self,
+    directory: &PathBuf,
+    password: &str,
+  ) -> Result<(), ConfigError> {
+    // verify the password
+    self.password.unlock(password.as_bytes())?;
+    // broken entries would become unreadable for good
+    if !self.corrupt.is_empty() {
+      return Err(ConfigError::CorruptError);
+    }
+
+    // setup the new file key
+    let key = Zeroizing::new(Encryption::generate(32));
+    let encryption = Encryption::from_base64(key.as_str())?;
+    let wrapped =
+      RawUserPassword::new(password.as_bytes(), key.as_str(), self.password.kdf.clone())?;
+
+    // the new blobs are removed with the next write, if anything fails
+    let mut written = Vec::new();
+    let passwords = match self.rekey_passwords(directory, &mut written) {
+      Ok(passwords) => passwords,
+      Err(error) => {
+        self.orphans.borrow_mut().extend(written);
+        return Err(error);
+      }
+    };
+
+    // the write encrypts the passwords and the backup with the new key
+    let previous_password = std::mem::replace(&mut self.password, wrapped);
+    let previous_encryption = self.encryption.replace(encryption);
+    let previous_passwords = std::mem::replace(&mut self.passwords, passwords);
+    if let Err(error) = self.write(directory) {
+      self.password = previous_password;
+      self.encryption = previous_encryption;
+      self.passwords = previous_passwords;
+      self.orphans.borrow_mut().extend(written);
+      return Err(error);
+    }
+
+    // the snapshots and the old blobs are still encrypted with the replaced keys
+    Snapshots::new(directory, self.file.as_str()).clear()?;
+    self
+      .orphans
+      .borrow_mut()
+      .extend(Self::attachment_ids(&previous_passwords));
+    self.remove_orphans(directory);
+    Ok(())
+  }
+
+  /// copy the passwords with new data keys and encrypt the blobs again with new keys
+  ///
+  /// the ids of the new blobs are collected, so they can be removed on a failure
+  fn rekey_passwords(
+    &self,
+    directory: &PathBuf,
+    written: &mut Vec<String>,
+  ) -> Result<Vec<PasswordType>, ConfigError> {
+    let attachments = Attachments::new(directory, self.file.as_str());
+    let mut passwords = self.passwords.clone();
+
+    for ty in passwords.iter_mut() {
+      if let PasswordType::Data(password) = ty {
+        password.key.zeroize();
+        password.key = Encryption::generate(32);
+
+        for attachment in password.attachments.iter_mut() {
+          let data = match attachments.read(attachment.id()) {
+            Ok(encrypted) => {
+              Encryption::from_base64(attachment.key())?.decrypt_bytes(encrypted.as_slice())?
+            }
+            // blobs which have never been synced to this device can not be encrypted again
+            Err(error) if error.kind() == ErrorKind::NotFound => continue,
+            Err(error) => return Err(error.into()),
+          };
+          let renewed = self.write_blob(directory, attachment.renew(), data.as_slice())?;
+          written.push(renewed.id().to_string());
+          *attachment = renewed;
+        }
+      }
+    }
+    Ok(passwords)
+  }
+
+  /// whether the metadata is encrypted
+  pub fn private_metadata(&self) -> bool {
+    self.private
+  }
+
+  /// switch between the plain and the private layout
+  ///
+  /// private vaults encrypt the metadata as well and are saved under an opaque id, which is only
+  /// listed in the encrypted index. the index can be searched for guessed usernames, so the
+  /// layout hides the metadata, but not the existence of the vault of a known username
+  pub fn update_private_metadata(
+    &mut self,
+    directory: &PathBuf,
+    enabled: bool,
+  ) -> Result<(), ConfigError> {
+    if self.private == enabled {
+      return Ok(());
+    }
+
+    let file = match enabled {
+      true => Uuid::new_v4().to_string(),
+      false => self.username.to_lowercase(),
+    };
+    if directory.join(&file).with_extension("json").exists() {
+      return Err(ConfigError::UserExistsError);
+    }
+    let lock = VaultLock::acquire(directory, file.as_str())?.ok_or(ConfigError::LockedError)?;
+
+    // the old file stays valid until the new one has been written
+    let previous = std::mem::replace(&mut self.file, file);
+    let fingerprint = self.fingerprint.replace(None);
+    self.private = enabled;
+    let result = self.write(directory).and_then(|_| {
+      let file = Some(self.file.as_str()).filter(|_| enabled);
+      VaultIndex::update(directory, self.username.as_str(), file).map_err(ConfigError::from)
+    });
+    if let Err(error) = result {
+      self.revert_layout(directory, previous, fingerprint);
+      return Err(error);
+    }
+
+    if let Err(error) = self.move_layout(directory, previous.as_str(), enabled) {
+      // the index has to point to the previous file again
+      let file = Some(previous.as_str()).filter(|_| !enabled);
+      let index = VaultIndex::update(directory, self.username.as_str(), file);
+      self.revert_layout(directory, previous, fingerprint);
+      index?;
+      return Err(error.into());
+    }
+    self._lock = Some(lock);
+    let _ = fs::remove_file(directory.join(&previous).with_extension("lock"));
+    Ok(())
+  }
+
+  /// move the blobs and remove everything named after the previous file
+  ///
+  /// the moved directories are moved back on a failure, so the previous file stays usable
+  fn move_layout(&self, directory: &PathBuf, previous: &str, enabled: bool) -> io::Result<()> {
+    let snapshots = directory.join("snapshots");
+    let mut parents = vec![directory.join("attachments")];
+    if !enabled {
+      parents.push(snapshots.clone());
+    }
+
+    let mut moved = Vec::new();
+    let result = parents
+      .iter()
+      .filter(|parent| parent.join(previous).exists())
+      .try_for_each(|parent| {
+        fs::rename(parent.join(previous), parent.join(&self.file))?;
+        moved.push(parent);
+        Ok(())
+      })
+      .and_then(|_| match enabled {
+        // the snapshots of the plain layout reveal the metadata
+        true if snapshots.join(previous).exists() => fs::remove_dir_all(snapshots.join(previous)),
+        _ => Ok(()),
+      })
+      .and_then(|_| fs::remove_file(directory.join(previous).with_extension("json")));
+
+    if result.is_err() {
+      for parent in moved.iter().rev() {
+        let _ = fs::rename(parent.join(&self.file), parent.join(previous));
+      }
+    }
+    result
+  }
+
+  /// switch back to the previous file after a failed change of the layout
+  fn revert_layout(&mut self, directory: &PathBuf, previous: String, fingerprint: Option<u64>) {
+    let _ = fs::remove_file(directory.join(&self.file).with_extension("json"));
+    self.file = previous;
+    self.fingerprint.set(fingerprint);
+    self.private = !self.private;
+  }
+
+  /// create new password
+  pub fn new_password(&mut self) -> PasswordData {
+    self.new_item(ItemKind::Login)
+  }
+
+  /// create new item of the given type
+  pub fn new_item(&mut self, kind: ItemKind) -> PasswordData {
+    let now = util::now();
+    let data = PasswordData {
+      login: None,
+      password: None,
+      url: None,
+      description: None,
+      folder: None,
+      tags: Vec::new(),
+      favorite: false,
+      fields: Vec::new(),
+      otp: None,
+      history: Vec::new(),
+      created: Some(now),
+      modified: Some(now),
+      password_changed: Some(now),
+      last_used: None,
+      deleted: None,
+      attachments: Vec::new(),
+      item: Item::new(kind),
+      uuid: Uuid::new_v4().to_string(),
+      key: Encryption::generate(32),
+      name: Some("Unnamed".to_string()),
+    };
+
+    // push the new password
+    self.passwords.push(PasswordType::Data(data.clone()));
+    data
+  }
+
+  /// update an specific password
+  pub fn update_password(&mut self, data: PasswordData) -> Result<(), ConfigError> {
+    // the values have to match the kind of the fields
+    for field in data.fields.iter() {
+      field.validate()?;
+    }
+    if let Some(otp) = data.otp.as_ref().filter(|otp| !otp.is_empty()) {
+      Otp::parse(otp)?;
+    }
+    self.check_folder(data.folder.as_ref())?;
+
+    // update an existing password
+    let retention = self.history_retention;
+    if let Ok(current) = self.password_mut(data.uuid.as_str()) {
+      let now = util::now();
+      // the history and the timestamps are only changed by the backend
+      let mut history = std::mem::take(&mut current.history);
+      let mut password_changed = current.password_changed;
+      if current.password.ne(&data.password) {
+        if let Some(previous) = current
+          .password
+          .as_ref()
+          .filter(|previous| !previous.is_empty())
+        {
+          history.insert(0, HistoryEntry::new(previous.clone()));
+        }
+        password_changed = Some(now);
+      }
+      history.truncate(retention);
+
+      let (created, last_used, deleted) = (current.created, current.last_used, current.deleted);
+      let attachments = std::mem::take(&mut current.attachments);
+      let key = std::mem::take(&mut current.key);
+      *current = data;
+      current.history = history;
+      current.attachments = attachments;
+      current.key = key;
+      current.created = created;
+      current.modified = Some(now);
+      current.password_changed = password_changed;
+      current.last_used = last_used;
+      current.deleted = deleted;
+    }
+    Ok(())
+  }
+
+  /// get the previous passwords of an entry, newest first
+  pub fn password_history(&self, uuid: &str) -> Result<Vec<HistoryEntry>, ConfigError> {
+    Ok(self.password(uuid)?.history.clone())
+  }
+
+  /// make a previous password the current one again
+  pub fn restore_password_history(
+    &mut self,
+    uuid: &str,
+    index: usize,
+  ) -> Result<PasswordData, ConfigError> {
+    let retention = self.history_retention;
+    let current = self.password_mut(uuid)?;
+    if index >= current.history.len() {
+      return Err(ConfigError::NotFoundError);
+    }
+
+    // the replaced password ends up in the history too
+    let entry = current.history.remove(index);
+    if let Some(previous) = current
+      .password
+      .take()
+      .filter(|previous| !previous.is_empty())
+    {
+      current.history.insert(0, HistoryEntry::new(previous));
+    }
+    current.password = Some(entry.password().clone());
+    current.history.truncate(retention);
+    current.modified = Some(util::now());
+    current.password_changed = current.modified;
+
+    Ok(current.clone())
+  }
+
+  /// get how many previous passwords are kept
+  pub fn history_retention(&self) -> usize {
+    self.history_retention
+  }
+
+  /// update how many previous passwords are kept, older ones are removed right away
+  pub fn update_history_retention(&mut self, retention: usize) {
+    self.history_retention = retention;
+    self.passwords.iter_mut().for_each(|ty| {
+      if let PasswordType::Data(password) = ty {
+        password.history.truncate(retention);
+      }
+    });
+  }
+
+  /// move an existing password into the trash
+  pub fn delete_password(&mut self, data: PasswordData) {
+    if let Ok(password) = self.password_mut(data.uuid.as_str()) {
+      password.deleted = Some(util::now());
+    }
+  }
+
+  /// get all passwords in the trash
+  pub fn trash(&self) -> Vec<PasswordData> {
+    self
+      .passwords
+      .iter()
+      .filter_map(|ty| match ty {
+        PasswordType::Data(password) if password.deleted.is_some() => Some(password.clone()),
+        _ => None,
+      })
+      .collect()
+  }
+
+  /// move a password out of the trash
+  pub fn restore_password(&mut self, uuid: &str) -> Result<PasswordData, ConfigError> {
+    let password = self.trashed_mut(uuid)?;
+    password.deleted = None;
+    Ok(password.clone())
+  }
+
+  /// delete a single or all passwords in the trash for good
+  pub fn purge_trash(&mut self, uuid: Option<&str>) {
+    let orphans = &self.orphans;
+    self.passwords.retain(|ty| match ty {
+      PasswordType::Data(password) if password.deleted.is_some() => {
+        let keep = uuid.is_some_and(|uuid| !password.uuid.eq(uuid));
+        if !keep {
+          Self::orphan_attachments(orphans, password);
+        }
+        keep
+      }
+      _ => true,
+    });
+  }
+
+  /// remember the blobs of a purged password for removal
+  fn orphan_attachments(orphans: &RefCell<Vec<String>>, password: &PasswordData) {
+    orphans.borrow_mut().extend(
+      password
+        .attachments
+        .iter()
+        .map(|attachment| attachment.id().to_string()),
+    );
+  }
+
+  /// purge the passwords which are in the trash for longer than the retention
+  ///
+  /// returns the number of purged passwords
+  fn purge_expired_trash(&mut self, now: u64) -> usize {
+    if self.trash_retention == 0 {
+      return 0;
+    }
+
+    let limit = now.saturating_sub(self.trash_retention.saturating_mul(DAY));
+    let count = self.passwords.len();
+    let orphans = &self.orphans;
+    self.passwords.retain(|ty| match ty {
+      PasswordType::Data(password) => {
+        let keep = password.deleted.is_none_or(|deleted| deleted > limit);
+        if !keep {
+          Self::orphan_attachments(orphans, password);
+        }
+        keep
+      }
+      PasswordType::Raw(_) => true,
+    });
+    count - self.passwords.len()
+  }
+
+  /// get the days until deleted passwords are purged
+  pub fn trash_retention(&self) -> u64 {
+    self.trash_retention
+  }
+
+  /// update the days until deleted passwords are purged, 0 keeps them forever
+  pub fn update_trash_retention(&mut self, retention: u64) {
+    self.trash_retention = retention
+  }
+
+  /// search the passwords outside of the trash
+  pub fn search_passwords(&self, query: &SearchQuery) -> Vec<PasswordSummary> {
+    // the analysis is expensive and only needed for some filters
+    let analysis = match query.weak || query.reused {
+      true => Some(self.analyse_passwords()),
+      false => None,
+    };
+    let folders = query
+      .folder
+      .as_ref()
+      .map(|uuid| folder::subtree(&self.folders, uuid));
+
+    let mut results = self
+      .passwords
+      .iter()
+      .filter_map(|ty| match ty {
+        PasswordType::Data(password) if password.deleted.is_none() => Some(password),
+        _ => None,
+      })
+      .filter(|password| match &folders {
+        Some(folders) => password
+          .folder
+          .as_ref()
+          .is_some_and(|uuid| folders.contains(uuid)),
+        None => true,
+      })
+      .filter(|password| query.kind.is_none_or(|kind| password.item.kind() == kind))
+      .filter(|password| !query.favorite || password.favorite)
+      .filter(|password| !query.otp || password.otp.as_ref().is_some_and(|otp| !otp.is_empty()))
+      .filter(|password| match &analysis {
+        Some(analysis) => {
+          (!query.weak
+            || analysis.weak.contains(&password.uuid)
+            || analysis.very_weak.contains(&password.uuid))
+            && (!query.reused || analysis.reused.contains(&password.uuid))
+        }
+        None => true,
+      })
+      .filter_map(|password| {
+        search::score(query.query.as_str(), &password.search_terms()).map(|score| (score, password))
+      })
+      .collect::<Vec<(u32, &PasswordData)>>();
+
+    let name = |password: &PasswordData| password.name.clone().unwrap_or_default().to_lowercase();
+    match query.sort {
+      SortOrder::Relevance => results.sort_by(|(first_score, first), (second_score, second)| {
+        second_score
+          .cmp(first_score)
+          .then_with(|| name(first).cmp(&name(second)))
+      }),
+      SortOrder::Name => results.sort_by_key(|(_, password)| name(password)),
+      // unknown timestamps last
+      SortOrder::Created => results.sort_by_key(|(_, password)| Reverse(password.created)),
+      SortOrder::Modified => results.sort_by_key(|(_, password)| Reverse(password.modified)),
+      SortOrder::LastUsed => results.sort_by_key(|(_, password)| Reverse(password.last_used)),
+    }
+
+    results
+      .into_iter()
+      .map(|(_, password)| password.summary())
+      .collect()
+  }
+
+  /// get the password of a single entry
+  pub fn reveal_password(&self, uuid: &str) -> Result<Option<String>, ConfigError> {
+    Ok(self.password(uuid)?.password.clone())
+  }
+
+  /// remember when a password has been used the last time
+  pub fn mark_used(&mut self, uuid: &str) -> Result<(), ConfigError> {
+    self.password_mut(uuid)?.last_used = Some(util::now());
+    Ok(())
+  }
+
+  /// get the days until a password is seen as old
+  pub fn password_max_age(&self) -> u64 {
+    self.password_max_age
+  }
+
+  /// update the days until a password is seen as old, 0 disables the check
+  pub fn update_password_max_age(&mut self, max_age: u64) {
+    self.password_max_age = max_age
+  }
+
+  /// encrypt a file and attach it to a password
+  ///
+  /// every blob has its own key, which is saved inside of the encrypted entry
+  pub fn add_attachment(
+    &mut self,
+    directory: &PathBuf,
+    uuid: &str,
+    name: String,
+    data: &[u8],
+  ) -> Result<Attachment, ConfigError> {
+    if data.len() > MAX_ATTACHMENT_SIZE {
+      return Err(ConfigError::AttachmentSizeError);
+    }
+    // fail before anything gets written
+    self.password(uuid)?;
+    let attachment = self.write_attachment(directory, name, data)?;
+
+    let password = self.password_mut(uuid)?;
+    password.attachments.push(attachment.clone());
+    password.modified = Some(util::now());
+    Ok(attachment)
+  }
+
+  /// encrypt the data with a new key and write it
+  fn write_attachment(
+    &self,
+    directory: &PathBuf,
+    name: String,
+    data: &[u8],
+  ) -> Result<Attachment, ConfigError> {
+    let attachment = Attachment::new(name, data.len() as u64, String::new());
+    self.write_blob(directory, attachment, data)
+  }
+
+  /// encrypt the data with a new key and save it as blob of the attachment
+  fn write_blob(
+    &self,
+    directory: &PathBuf,
+    mut attachment: Attachment,
+    data: &[u8],
+  ) -> Result<Attachment, ConfigError> {
+    let key = Zeroizing::new(Encryption::generate(32));
+    let encrypted = Encryption::from_base64(key.as_str())?.encrypt_bytes(data)?;
+    attachment.set_key(key.to_string());
+    Attachments::new(directory, self.file.as_str()).write(attachment.id(), encrypted.as_slice())?;
+    Ok(attachment)
+  }
+
+  /// get the attachments of a password
+  pub fn attachments(&self, uuid: &str) -> Result<Vec<Attachment>, ConfigError> {
+    Ok(self.password(uuid)?.attachments.clone())
+  }
+
+  /// decrypt the content of an attachment
+  pub fn read_attachment(
+    &self,
+    directory: &PathBuf,
+    uuid: &str,
+    id: &str,
+  ) -> Result<Zeroizing<Vec<u8>>, ConfigError> {
+    let attachment = self
+      .password(uuid)?
+      .attachments
+      .iter()
+      .find(|attachment| attachment.id().eq(id))
+      .ok_or(ConfigError::NotFoundError)?;
+
+    let encrypted = Attachments::new(directory, self.file.as_str()).read(id)?;
+    Ok(Encryption::from_base64(attachment.key())?.decrypt_bytes(encrypted.as_slice())?)
+  }
+
+  /// detach a file, the blob gets removed with the next write
+  pub fn delete_attachment(&mut self, uuid: &str, id: &str) -> Result<(), ConfigError> {
+    let password = self.password_mut(uuid)?;
+    let count = password.attachments.len();
+    password
+      .attachments
+      .retain(|attachment| !attachment.id().eq(id));
+    if password.attachments.len() == count {
+      return Err(ConfigError::NotFoundError);
+    }
+    password.modified = Some(util::now());
+
+    self.orphans.borrow_mut().push(id.to_string());
+    Ok(())
+  }
+
+  /// add the entries of an export, entries which already exist in the vault are skipped
+  ///
+  /// the attachments of the entries are written into the directory right away, the entries and
+  /// folders are only added once all of them have been written
+  pub fn import_passwords(
+    &mut self,
+    directory: &PathBuf,
+    import: Import,
+  ) -> Result<ImportReport, ConfigError> {
+    let mut known = self
+      .passwords
+      .iter()
+      .filter_map(|ty| match ty {
+        PasswordType::Data(password) if password.deleted.is_none() => {
+          Some(password.duplicate_key())
+        }
+        _ => None,
+      })
+      .collect::<HashSet<u64>>();
+    let mut report = ImportReport {
+      skipped: import.skipped,
+      ..ImportReport::default()
+    };
+    // nothing is added to the vault before all blobs are written
+    let mut passwords = Vec::new();
+    let mut folders = Vec::new();
+
+    for entry in import.entries.iter() {
+      let created = entry.created.unwrap_or_else(util::now);
+      let modified = entry.modified.unwrap_or(created);
+      // values which do not match the kind of their field are kept as text
+      let mut fields = entry
+        .fields
+        .iter()
+        .cloned()
+        .map(CustomField::or_text)
+        .collect::<Vec<CustomField>>();
+      // unreadable one time passwords are kept as field
+      let otp = match &entry.otp {
+        Some(otp) if Otp::parse(otp).is_err() => {
+          fields.push(CustomField::new(
+            String::from("otp"),
+            otp.clone(),
+            FieldKind::Hidden,
+          ));
+          None
+        }
+        otp => otp.clone(),
+      };
+
+      let mut data = PasswordData {
+        name: entry.name.clone().or_else(|| Some("Unnamed".to_string())),
+        login: entry.login.clone(),
+        password: entry.password.clone(),
+        url: entry.url.clone(),
+        description: entry.description.clone(),
+        folder: None,
+        tags: entry.tags.clone(),
+        favorite: entry.favorite,
+        fields,
+        otp,
+        history: entry
+          .history
+          .iter()
+          .take(self.history_retention)
+          .map(|password| HistoryEntry::new(password.clone()))
+          .collect(),
+        created: Some(created),
+        modified: Some(modified),
+        password_changed: Some(modified),
+        last_used: None,
+        deleted: None,
+        attachments: Vec::new(),
+        item: entry.item.clone(),
+        uuid: Uuid::new_v4().to_string(),
+        key: Encryption::generate(32),
+      };
+      if !known.insert(data.duplicate_key()) {
+        report.duplicates += 1;
+        continue;
+      }
+
+      for imported in entry.attachments.iter() {
+        match self.write_attachment(directory, imported.name.clone(), &imported.data) {
+          Ok(attachment) => data.attachments.push(attachment),
+          Err(error) => {
+            // the blobs written so far are not referenced by the vault
+            self.orphans.borrow_mut().extend(
+              passwords
+                .iter()
+                .chain(std::iter::once(&data))
+                .flat_map(|password: &PasswordData| password.attachments.iter())
+                .map(|attachment| attachment.id().to_string()),
+            );
+            return Err(error);
+          }
+        }
+      }
+
+      data.folder = self.import_folder(&entry.folder, &mut folders);
+      passwords.push(data);
+      report.imported += 1;
+    }
+
+    self.folders.extend(folders);
+    self
+      .passwords
+      .extend(passwords.into_iter().map(PasswordType::Data));
+    Ok(report)
+  }
+
+  /// find the folders of an imported path, missing ones are added to the staged folders
+  fn import_folder(&self, path: &[String], staged: &mut Vec<Folder>) -> Option<String> {
+    let mut parent: Option<String> = None;
+    for name in path {
+      let existing = self
+        .folders
+        .iter()
+        .chain(staged.iter())
+        .find(|folder| folder.parent() == parent.as_ref() && folder.name().eq(name))
+        .map(|folder| folder.uuid().to_string());
+
+      parent = Some(match existing {
+        Some(uuid) => uuid,
+        None => {
+          let folder = Folder::new(name.clone(), parent.clone());
+          let uuid = folder.uuid().to_string();
+          staged.push(folder);
+          uuid
+        }
+      });
+    }
+    parent
+  }
+
+  /// get all folders
+  pub fn folders(&self) -> Vec<Folder> {
+    self.folders.clone()
+  }
+
+  /// make sure the folder exists, none is the top level
+  fn check_folder(&self, folder: Option<&String>) -> Result<(), ConfigError> {
+    match folder {
+      Some(uuid) if !self.folders.iter().any(|folder| folder.uuid().eq(uuid)) => {
+        Err(ConfigError::NotFoundError)
+      }
+      _ => Ok(()),
+    }
+  }
+
+  /// get a folder by its uuid
+  fn folder_mut(&mut self, uuid: &str) -> Result<&mut Folder, ConfigError> {
+    self
+      .folders
+      .iter_mut()
+      .find(|folder| folder.uuid().eq(uuid))
+      .ok_or(ConfigError::NotFoundError)
+  }
+
+  /// create new folder
+  pub fn new_folder(
+    &mut self,
+    name: String,
+    parent: Option<String>,
+  ) -> Result<Folder, ConfigError> {
+    self.check_folder(parent.as_ref())?;
+
+    let folder = Folder::new(name, parent);
+    self.folders.push(folder.clone());
+    Ok(folder)
+  }
+
+  /// rename an existing folder
+  pub fn rename_folder(&mut self, uuid: &str, name: String) -> Result<(), ConfigError> {
+    self.folder_mut(uuid)?.rename(name);
+    Ok(())
+  }
+
+  /// move a folder with all its content into another one
+  pub fn move_folder(&mut self, uuid: &str, parent: Option<String>) -> Result<(), ConfigError> {
+    self.check_folder(parent.as_ref())?;
+    // the new parent can not be the folder itself or one of its subfolders
+    if let Some(parent) = &parent {
+      if folder::subtree(&self.folders, uuid).contains(parent) {
+        return Err(ConfigError::FolderCycleError);
+      }
+    }
+
+    self.folder_mut(uuid)?.set_parent(parent);
+    Ok(())
+  }
+
+  /// delete a folder, its subfolders and passwords are moved up to its parent
+  pub fn delete_folder(&mut self, uuid: &str) -> Result<(), ConfigError> {
+    let parent = self.folder_mut(uuid)?.parent().cloned();
+    self.folders.retain(|folder| !folder.uuid().eq(uuid));
+
+    let uuid = uuid.to_string();
+    self
+      .folders
+      .iter_mut()
+      .filter(|folder| folder.parent().eq(&Some(&uuid)))
+      .for_each(|folder| folder.set_parent(parent.clone()));
+    self.passwords.iter_mut().for_each(|ty| {
+      if let PasswordType::Data(password) = ty {
+        if password.folder.eq(&Some(uuid.clone())) {
+          password.folder = parent.clone();
+        }
+      }
+    });
+    Ok(())
+  }
+
+  /// move a password into a folder, none is the top level
+  pub fn move_password(&mut self, uuid: &str, folder: Option<String>) -> Result<(), ConfigError> {
+    self.check_folder(folder.as_ref())?;
+
+    self.password_mut(uuid)?.folder = folder;
+    Ok(())
+  }
+
+  /// get a decrypted password by its uuid
+  fn password(&self, uuid: &str) -> Result<&PasswordData, ConfigError> {
+    self
+      .passwords
+      .iter()
+      .find_map(|ty| match ty {
+        PasswordType::Data(password) if password.uuid.eq(uuid) => Some(password),
+        _ => None,
+      })
+      .ok_or(ConfigError::NotFoundError)
+  }
+
+  /// the passwords in the trash can only be restored or purged
+  fn password_mut(&mut self, uuid: &str) -> Result<&mut PasswordData, ConfigError> {
+    self
+      .passwords
+      .iter_mut()
+      .find_map(|ty| match ty {
+        PasswordType::Data(password) if password.uuid.eq(uuid) && password.deleted.is_none() => {
+          Some(password)
+        }
+        _ => None,
+      })
+      .ok_or(ConfigError::NotFoundError)
+  }
+
+  fn trashed_mut(&mut self, uuid: &str) -> Result<&mut PasswordData, ConfigError> {
+    self
+      .passwords
+      .iter_mut()
+      .find_map(|ty| match ty {
+        PasswordType::Data(password) if password.uuid.eq(uuid) && password.deleted.is_some() => {
+          Some(password)
+        }
+        _ => None,
+      })
+      .ok_or(ConfigError::NotFoundError)
+  }
+
+  /// get the current one time password of an entry
+  pub fn otp_code(&self, uuid: &str) -> Result<OtpCode, ConfigError> {
+    let otp = self
+      .password(uuid)?
+      .otp
+      .as_ref()
+      .ok_or(ConfigError::NotFoundError)?;
+    Ok(Otp::parse(otp)?.now())
+  }
+
+  /// increase the counter of a hotp entry and get the new code
+  pub fn next_hotp(&mut self, uuid: &str) -> Result<OtpCode, ConfigError> {
+    let password = self.password_mut(uuid)?;
+    let otp = password.otp.as_ref().ok_or(ConfigError::NotFoundError)?;
+    let next = otp::increment_counter(otp)?;
+    let code = Otp::parse(next.as_str())?.now();
+
+    password.otp = Some(next);
+    Ok(code)
   }
 
   pub fn analyse_passwords(&self) -> AnalyseResult {
@@ -394,122 +2221,986 @@ impl User {
     let mut medium = Vec::new();
     let mut weak = Vec::new();
     let mut very_weak = Vec::new();
+    let mut old = Vec::new();
+    // passwords changed before are old
+    let limit = match self.password_max_age {
+      0 => None,
+      max_age => Some(util::now().saturating_sub(max_age.saturating_mul(DAY))),
+    };
 
-    self.passwords.clone().into_iter().for_each(|ty| {
+    self.passwords().into_iter().for_each(|ty| {
       if let PasswordType::Data(password) = ty.clone() {
-        self.passwords.clone().into_iter().for_each(|cty| {
+        // entries without password can not be analysed
+        let secret = match password.secret() {
+          Some(secret) if !secret.is_empty() => secret,
+          _ => return,
+        };
+
+        self.passwords().into_iter().for_each(|cty| {
           if let PasswordType::Data(compare) = cty.clone() {
-            if compare
-              .password
-              .as_ref()
-              .unwrap()
-              .eq(password.password.as_ref().unwrap())
-              && !compare.uuid.eq(&password.uuid)
-            {
+            if compare.secret().eq(&Some(secret)) && !compare.uuid.eq(&password.uuid) {
               reused.push(compare.uuid.clone());
               reused.push(password.uuid.clone());
             }
           }
         });
 
-        // sort by strength
-        match zxcvbn::zxcvbn(password.password.as_ref().unwrap().as_str(), &[])
-          .unwrap()
-          .score()
+        // entries without any timestamp are older than the tracking, but of unknown age
+        if let (Some(limit), Some(changed)) =
+          (limit, password.password_changed.or(password.created))
         {
-          0 => very_weak.push(password.uuid.clone()),
-          1 => weak.push(password.uuid.clone()),
-          2 => medium.push(password.uuid.clone()),
-          3 => strong.push(password.uuid.clone()),
-          4 => very_strong.push(password.uuid.clone()),
+          if changed < limit {
+            old.push(password.uuid.clone());
+          }
+        }
+
+        // sort by strength
+        match zxcvbn::zxcvbn(secret.as_str(), &[]).map(|entropy| entropy.score()) {
+          Ok(0) => very_weak.push(password.uuid.clone()),
+          Ok(1) => weak.push(password.uuid.clone()),
+          Ok(2) => medium.push(password.uuid.clone()),
+          Ok(3) => strong.push(password.uuid.clone()),
+          Ok(4) => very_strong.push(password.uuid.clone()),
           // should never happen
           _ => {}
         }
       }
     });
 
-    AnalyseResult {
-      reused,
-      very_strong,
-      strong,
-      medium,
-      weak,
-      very_weak,
+    AnalyseResult {
+      reused,
+      very_strong,
+      strong,
+      medium,
+      weak,
+      very_weak,
+      old,
+    }
+  }
+
+  pub fn backup(&self) -> Option<Backup> {
+    self.backup.clone()
+  }
+
+  /// get the backup status without the credentials
+  pub fn backup_status(&self) -> BackupStatus {
+    match &self.backup {
+      Some(backup) => backup.status(),
+      None => BackupStatus::default(),
+    }
+  }
+
+  /// enable the cloud backup on the given worker
+  ///
+  /// links an existing backup account if credentials are given, creates a new one otherwise
+  pub fn enable_backup(
+    &mut self,
+    directory: &PathBuf,
+    url: String,
+    credentials: Option<(String, String)>,
+  ) -> Result<(), ConfigError> {
+    let backup = match credentials {
+      Some((uuid, password)) => Backup::new(url, uuid, password),
+      None => {
+        // the backup password is never typed by the user
+        let password = Encryption::generate(32);
+        let uuid = SyncClient::new(url.as_str()).signup(password.as_str())?;
+        Backup::new(url, uuid, password)
+      }
+    };
+    // verify the credentials before saving them
+    backup.client()?;
+
+    self.apply(directory, |user| {
+      user.backup = Some(backup);
+      Ok(())
+    })
+  }
+
+  /// upload the encrypted vault to the worker, the blobs of the attachments stay local
+  pub fn push_backup(&self) -> Result<(), ConfigError> {
+    let backup = self.backup.as_ref().ok_or(ConfigError::BackupError)?;
+    // the uploaded data equals the file on the disk
+    let raw = serde_json::to_string(&RawUser::from(self))?;
+
+    backup.client()?.push(raw.as_str())?;
+    Ok(())
+  }
+
+  /// replace the local vault with the one from the worker
+  ///
+  /// the local vault is saved first, so it stays available as snapshot. the blobs of the
+  /// attachments are not synced, attachments added on other devices stay missing
+  pub fn pull_backup(&mut self, directory: &PathBuf) -> Result<(), ConfigError> {
+    let backup = self.backup.as_ref().ok_or(ConfigError::BackupError)?;
+    // a new account has no data yet
+    let raw = backup
+      .client()?
+      .pull()?
+      .ok_or(ConfigError::EmptyBackupError)?;
+
+    // the remote vault has to share the file key
+    let encryption = self.encryption.as_ref().unwrap();
+    let raw = RawUser::parse(raw.as_str())?.unseal(encryption)?;
+    let passwords = Self::decrypt_passwords(&raw.passwords, encryption)?;
+    let folders = Self::decrypt_folders(&raw.folders, encryption)?;
+
+    // keep the local changes which have not been pushed as snapshot
+    self.write(directory)?;
+    self.apply(directory, |user| {
+      user.replace_passwords(passwords);
+      user.folders = folders;
+      user.generator = raw.generator;
+      user.auto_lock = raw.auto_lock;
+      user.history_retention = raw.history_retention;
+      user.trash_retention = raw.trash_retention;
+      user.password_max_age = raw.password_max_age;
+      Ok(())
+    })
+  }
+
+  /// get all passwords which are not in the trash
+  pub fn passwords(&self) -> Vec<PasswordType> {
+    self
+      .passwords
+      .iter()
+      .filter(|ty| !matches!(ty, PasswordType::Data(password) if password.deleted.is_some()))
+      .cloned()
+      .collect()
+  }
+
+  /// wipe the decrypted data, the user can not be used afterwards
+  pub fn wipe(&mut self) {
+    self.passwords.zeroize();
+    self.folders.zeroize();
+    self.backup = None;
+    self.encryption = None;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::model::sync::testing::MockWorker;
+  use tempfile::TempDir;
+
+  #[test]
+  fn test_signup() {
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    User::new_from_signup(&dir.as_ref().to_path_buf(), data).unwrap();
+    dir.close().unwrap();
+  }
+
+  #[test]
+  fn test_login() {
+    let mut data = UserData {
+      username: String::from("Username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let user = User::new_from_signup(&dir.as_ref().to_path_buf(), data.clone()).unwrap();
+
+    // test case
+    data.username = String::from("UseRname");
+
+    let username = user.username();
+    // release the lock of the signup session
+    drop(user);
+
+    let login = User::new_from_login(&dir.as_ref().to_path_buf(), data).unwrap();
+    dir.close().unwrap();
+    assert_eq!(username, login.username);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_auth() {
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    User::new_from_signup(&dir.as_ref().to_path_buf(), data.clone()).unwrap();
+
+    User::new_from_login(
+      &dir.as_ref().to_path_buf(),
+      UserData {
+        username: String::from("username"),
+        password: String::from("te"),
+      },
+    )
+    .unwrap();
+  }
+
+  #[test]
+  fn test_kdf_upgrade() {
+    let data = UserData {
+      username: String::from("Legacy"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    // vault written with pbkdf2 before the kdf descriptor
+    fs::write(
+      path.join("legacy.json"),
+      include_str!("../../tests/fixtures/vault_v0.json"),
+    )
+    .unwrap();
+
+    let user = User::new_from_login(&path, data.clone()).unwrap();
+    assert_eq!(1, user.passwords().len());
+    drop(user);
+
+    // upgraded on the login
+    let (raw, _) = RawUser::new_from_disk(&path, "legacy").unwrap();
+    assert!(!raw.password.outdated());
+    assert!(raw
+      .passwords
+      .iter()
+      .all(|password| !Encryption::is_legacy(password.iv.as_str())));
+
+    // still opens with the same password
+    let user = User::new_from_login(&path, data).unwrap();
+    assert_eq!(1, user.passwords().len());
+  }
+
+  #[test]
+  fn test_migrations() {
+    // every historical format, saved by "Legacy" with the password "password"
+    let fixtures = [
+      include_str!("../../tests/fixtures/vault_v0.json"),
+      include_str!("../../tests/fixtures/vault_v1.json"),
+      include_str!("../../tests/fixtures/vault_v2.json"),
+      include_str!("../../tests/fixtures/vault_v3.json"),
+      include_str!("../../tests/fixtures/vault_v4.json"),
+      include_str!("../../tests/fixtures/vault_v5.json"),
+      include_str!("../../tests/fixtures/vault_v6.json"),
+      include_str!("../../tests/fixtures/vault_v7.json"),
+      include_str!("../../tests/fixtures/vault_v8.json"),
+      include_str!("../../tests/fixtures/vault_v9.json"),
+      include_str!("../../tests/fixtures/vault_v10.json"),
+      include_str!("../../tests/fixtures/vault_v11.json"),
+      include_str!("../../tests/fixtures/vault_v12.json"),
+    ];
+    assert_eq!(fixtures.len(), FORMAT_VERSION as usize + 1);
+
+    for fixture in fixtures {
+      let dir = TempDir::new().unwrap();
+      let path = dir.as_ref().to_path_buf();
+      fs::write(path.join("legacy.json"), fixture).unwrap();
+
+      let user = User::new_from_login(
+        &path,
+        UserData {
+          username: String::from("Legacy"),
+          password: String::from("password"),
+        },
+      )
+      .unwrap();
+      assert_eq!(
+        "6f1c1a4e-2a8e-4a55-9d2b-6d3f0f7c9b10",
+        user.backup().unwrap().uuid()
+      );
+      match user.passwords().first().unwrap() {
+        PasswordType::Data(password) => {
+          assert_eq!("legacy@example.com", password.login.as_ref().unwrap());
+          assert_eq!("hunter2", password.password.as_ref().unwrap());
+        }
+        _ => panic!("Wrong enum"),
+      }
+
+      // saved with the current version
+      user.write(&path).unwrap();
+      let content = fs::read_to_string(path.join("legacy.json")).unwrap();
+      let raw = serde_json::from_str::<Value>(content.as_str()).unwrap();
+      assert_eq!(Some(FORMAT_VERSION), raw["format_version"].as_u64());
+    }
+  }
+
+  #[test]
+  fn test_unsupported_version() {
+    let mut raw =
+      serde_json::from_str::<Value>(include_str!("../../tests/fixtures/vault_v1.json")).unwrap();
+    raw["format_version"] = Value::from(FORMAT_VERSION + 1);
+
+    assert!(matches!(
+      RawUser::parse(raw.to_string().as_str()),
+      Err(ConfigError::VersionError(version)) if version == FORMAT_VERSION + 1
+    ));
+    assert!(matches!(
+      RawUser::parse("[]"),
+      Err(ConfigError::FormatError)
+    ));
+  }
+
+  #[test]
+  fn test_snapshots() {
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let mut user = User::new_from_signup(&path, data).unwrap();
+    assert!(user.list_snapshots(&path).unwrap().is_empty());
+
+    user.new_password();
+    user.update_history_retention(1);
+    user.update_trash_retention(1);
+    user.update_password_max_age(1);
+    user.write(&path).unwrap();
+    // the state right after the signup
+    let snapshots = user.list_snapshots(&path).unwrap();
+    assert_eq!(1, snapshots.len());
+
+    user
+      .restore_snapshot(&path, snapshots[0].id().as_str())
+      .unwrap();
+    assert_eq!(0, user.passwords().len());
+    // the settings are restored with the data
+    assert_eq!(history::DEFAULT_RETENTION, user.history_retention());
+    assert_eq!(DEFAULT_TRASH_RETENTION, user.trash_retention());
+    assert_eq!(DEFAULT_PASSWORD_MAX_AGE, user.password_max_age());
+    // the restore can be undone
+    assert_eq!(2, user.list_snapshots(&path).unwrap().len());
+
+    assert!(user.restore_snapshot(&path, "../username").is_err());
+  }
+
+  #[test]
+  fn test_single_session() {
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let user = User::new_from_signup(&path, data.clone()).unwrap();
+
+    assert!(matches!(
+      User::new_from_login(&path, data.clone()),
+      Err(ConfigError::LockedError)
+    ));
+    // a new login is only allowed to release the lock with the right password
+    assert!(user.verify_login(&data));
+    assert!(!user.verify_login(&UserData {
+      username: String::from("username"),
+      password: String::from("wrong"),
+    }));
+    drop(user);
+    User::new_from_login(&path, data).unwrap();
+  }
+
+  #[test]
+  fn test_conflict() {
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let mut user = User::new_from_signup(&path, data).unwrap();
+
+    // modified by someone else
+    let file = path.join("username.json");
+    fs::write(&file, "external").unwrap();
+
+    user.new_password();
+    assert!(matches!(user.write(&path), Err(ConfigError::ConflictError)));
+    assert_eq!("external", fs::read_to_string(&file).unwrap());
+  }
+
+  #[test]
+  fn test_apply() {
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let mut user = User::new_from_signup(&path, data).unwrap();
+    let folder = user
+      .apply(&path, |user| user.new_folder(String::from("Work"), None))
+      .unwrap();
+    let password = user.apply(&path, |user| Ok(user.new_password())).unwrap();
+
+    // a failed change is not applied
+    assert!(user
+      .apply(&path, |user| user
+        .move_folder(folder.uuid(), Some(String::from("missing"))))
+      .is_err());
+    assert_eq!(None, user.folders()[0].parent());
+
+    // modified by someone else
+    fs::write(path.join("username.json"), "external").unwrap();
+    assert!(matches!(
+      user.apply(&path, |user| user
+        .rename_folder(folder.uuid(), String::from("Renamed"))),
+      Err(ConfigError::ConflictError)
+    ));
+    assert_eq!("Work", user.folders()[0].name());
+    assert!(matches!(
+      user.apply(&path, |user| {
+        user.update_trash_retention(1);
+        user.purge_trash(None);
+        Ok(())
+      }),
+      Err(ConfigError::ConflictError)
+    ));
+    assert_eq!(DEFAULT_TRASH_RETENTION, user.trash_retention());
+
+    // the blob of an attachment which could not be saved is removed later
+    let result = user.apply(&path, |user| {
+      user.add_attachment(
+        &path,
+        password.uuid.as_str(),
+        String::from("file.txt"),
+        b"content",
+      )
+    });
+    assert!(matches!(result, Err(ConfigError::ConflictError)));
+    assert!(user.attachments(password.uuid.as_str()).unwrap().is_empty());
+    assert_eq!(1, user.orphans.borrow().len());
+  }
+
+  #[test]
+  fn test_corrupt_entries() {
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let mut user = User::new_from_signup(&path, data.clone()).unwrap();
+    user.new_password();
+    user.new_password();
+    user.write(&path).unwrap();
+    drop(user);
+
+    // damage the first entry
+    let file = path.join("username.json");
+    let mut raw =
+      serde_json::from_str::<Value>(fs::read_to_string(&file).unwrap().as_str()).unwrap();
+    raw["passwords"][0]["data"] = Value::from("#");
+    fs::write(&file, raw.to_string()).unwrap();
+
+    let mut user = User::new_from_login(&path, data.clone()).unwrap();
+    assert_eq!(2, user.passwords().len());
+    let corrupt = user.corrupt_entries();
+    assert_eq!(1, corrupt.len());
+    assert!(matches!(user.passwords()[0], PasswordType::Raw(_)));
+    assert!(user.analyse_passwords().weak.is_empty());
+
+    // kept untouched on writes
+    user.write(&path).unwrap();
+    assert_eq!("#", user.export_corrupt_entries()[0].data);
+    assert!(matches!(
+      user.rotate_vault_key(&path, "password"),
+      Err(ConfigError::CorruptError)
+    ));
+
+    user.delete_corrupt_entry(corrupt[0].id.as_str());
+    user.write(&path).unwrap();
+    drop(user);
+
+    let user = User::new_from_login(&path, data).unwrap();
+    assert_eq!(1, user.passwords().len());
+    assert!(user.corrupt_entries().is_empty());
+  }
+
+  #[test]
+  fn test_change_password() {
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let mut user = User::new_from_signup(&path, data.clone()).unwrap();
+    let mut password = user.new_password();
+    password.password = Some(String::from("test"));
+    user.update_password(password).unwrap();
+
+    // wrong current password
+    assert!(user.change_password(&path, "wrong", "new").is_err());
+    user.change_password(&path, "password", "new").unwrap();
+    drop(user);
+
+    // the old password does not work anymore
+    assert!(User::new_from_login(&path, data).is_err());
+    let user = User::new_from_login(
+      &path,
+      UserData {
+        username: String::from("username"),
+        password: String::from("new"),
+      },
+    )
+    .unwrap();
+    assert_eq!(1, user.passwords().len());
+  }
+
+  #[test]
+  fn test_rotate_vault_key() {
+    let worker = MockWorker::start();
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let mut user = User::new_from_signup(&path, data.clone()).unwrap();
+    user.enable_backup(&path, worker.url.clone(), None).unwrap();
+    let uuid = user.backup().unwrap().uuid();
+    let mut password = user.new_password();
+    password.password = Some(String::from("test"));
+    user.update_password(password.clone()).unwrap();
+    let attachment = user
+      .add_attachment(&path, password.uuid.as_str(), String::from("a"), b"data")
+      .unwrap();
+    user.write(&path).unwrap();
+
+    let (old, _) = RawUser::new_from_disk(&path, "username").unwrap();
+    assert!(!user.list_snapshots(&path).unwrap().is_empty());
+    assert!(user.rotate_vault_key(&path, "wrong").is_err());
+    user.rotate_vault_key(&path, "password").unwrap();
+    // nothing encrypted with the old key is kept
+    assert!(user.list_snapshots(&path).unwrap().is_empty());
+    let blobs = Attachments::new(&path, "username");
+    assert_eq!(1, blobs.list().unwrap().len());
+    assert!(blobs.read(attachment.id()).is_err());
+    // the blob has been encrypted again with a new key
+    let rotated = user.attachments(password.uuid.as_str()).unwrap().remove(0);
+    assert_ne!(attachment.key(), rotated.key());
+    let blob = blobs.read(rotated.id()).unwrap();
+    let old_key = Encryption::from_base64(attachment.key()).unwrap();
+    assert!(old_key.decrypt_bytes(blob.as_slice()).is_err());
+    assert_eq!(
+      b"data".to_vec(),
+      user
+        .read_attachment(&path, password.uuid.as_str(), rotated.id())
+        .unwrap()
+        .to_vec()
+    );
+    drop(user);
+
+    // the old key can not decrypt the new entries
+    let (new, _) = RawUser::new_from_disk(&path, "username").unwrap();
+    assert_ne!(old.password.key, new.password.key);
+    let key = old.password.unlock("password".as_bytes()).unwrap();
+    let encryption = Encryption::from_base64(key.as_str()).unwrap();
+    assert!(User::decrypt_passwords(&new.passwords, &encryption).is_err());
+
+    let user = User::new_from_login(&path, data).unwrap();
+    assert_eq!(1, user.passwords().len());
+    assert_eq!(uuid, user.backup().unwrap().uuid());
+  }
+
+  #[test]
+  fn test_data_keys() {
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let mut user = User::new_from_signup(&path, data.clone()).unwrap();
+    let mut password = user.new_password();
+    password.password = Some(String::from("test"));
+    user.update_password(password.clone()).unwrap();
+
+    // entries written before the data keys are encrypted with the file key
+    let legacy = encrypt_entry(&password, user.encryption.as_ref().unwrap());
+    assert!(legacy.key.is_none());
+    user.passwords.push(PasswordType::Raw(legacy));
+    user.write(&path).unwrap();
+    drop(user);
+
+    let mut user = User::new_from_login(&path, data.clone()).unwrap();
+    assert!(user.corrupt_entries().is_empty());
+    assert_eq!(2, user.passwords().len());
+    user.write(&path).unwrap();
+    // every entry has its own key now
+    let (raw, _) = RawUser::new_from_disk(&path, "username").unwrap();
+    let keys = raw
+      .passwords
+      .iter()
+      .map(|password| password.key.as_ref().unwrap().key.clone())
+      .collect::<Vec<String>>();
+    assert_eq!(2, keys.len());
+    assert_ne!(keys[0], keys[1]);
+
+    // the rotation of the file key replaces the data keys too
+    let before = user.password(password.uuid.as_str()).unwrap().key.clone();
+    user.rotate_vault_key(&path, "password").unwrap();
+    drop(user);
+    let user = User::new_from_login(&path, data).unwrap();
+    let after = user.password(password.uuid.as_str()).unwrap().key.clone();
+    assert_ne!(before, after);
+    // the old data key can not decrypt the rotated entry
+    let (raw, _) = RawUser::new_from_disk(&path, "username").unwrap();
+    let old = Encryption::from_base64(before.as_str()).unwrap();
+    assert!(raw
+      .passwords
+      .iter()
+      .all(|entry| User::decrypt_entry::<OpenedPassword>(entry, &old).is_err()));
+    // the frontend never sees the key
+    let json = serde_json::to_string(&user.passwords()).unwrap();
+    assert!(!json.contains(after.as_str()));
+  }
+
+  #[test]
+  fn test_private_metadata() {
+    let data = UserData {
+      username: String::from("Alice"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let mut user = User::new_from_signup(&path, data.clone()).unwrap();
+    let password = user.new_password();
+    user
+      .add_attachment(&path, password.uuid.as_str(), String::from("file"), b"data")
+      .unwrap();
+    user.write(&path).unwrap();
+
+    user.update_private_metadata(&path, true).unwrap();
+    assert!(user.private_metadata());
+    // nothing is named after the user anymore
+    for file in [
+      "alice.json",
+      "alice.lock",
+      "attachments/alice",
+      "snapshots/alice",
+    ] {
+      assert!(!path.join(file).exists());
     }
+    let file = path.join(&user.file).with_extension("json");
+    let content = fs::read_to_string(&file).unwrap();
+    assert!(!content.to_lowercase().contains("alice"));
+    let raw = RawUser::parse(content.as_str()).unwrap();
+    assert!(raw.passwords.is_empty());
+    // the ciphertext ends with the 16 byte tag
+    let ciphertext = base64::decode(&raw.private.as_ref().unwrap().ciphertext).unwrap();
+    assert_eq!(0, (ciphertext.len() - 16) % PRIVATE_PADDING);
+    drop(user);
+
+    // the signup finds the vault in the index
+    assert!(matches!(
+      User::new_from_signup(&path, data.clone()),
+      Err(ConfigError::UserExistsError)
+    ));
+    let mut user = User::new_from_login(&path, data.clone()).unwrap();
+    assert!(user.private_metadata());
+    assert_eq!("Alice", user.username());
+    assert_eq!(1, user.passwords().len());
+    assert_eq!(
+      b"data".to_vec(),
+      user
+        .read_attachment(
+          &path,
+          password.uuid.as_str(),
+          user.attachments(password.uuid.as_str()).unwrap()[0].id()
+        )
+        .unwrap()
+        .to_vec()
+    );
+
+    // a failed move keeps the private vault usable
+    let private = user.file.clone();
+    fs::create_dir_all(path.join("attachments/alice/blocked")).unwrap();
+    assert!(user.update_private_metadata(&path, false).is_err());
+    assert!(user.private_metadata());
+    assert!(!path.join("alice.json").exists());
+    assert!(path.join("attachments").join(&private).exists());
+    assert_eq!(
+      Some(private.clone()),
+      VaultIndex::find(&path, "alice").unwrap()
+    );
+    fs::remove_dir_all(path.join("attachments/alice")).unwrap();
+    user.write(&path).unwrap();
+
+    // and back
+    user.update_private_metadata(&path, false).unwrap();
+    assert!(!path.join(&private).with_extension("json").exists());
+    assert!(path.join("alice.json").exists());
+    assert_eq!(None, VaultIndex::find(&path, "alice").unwrap());
+    drop(user);
+    let user = User::new_from_login(&path, data).unwrap();
+    assert!(!user.private_metadata());
   }
 
-  pub fn backup(&self) -> Option<Backup> {
-    self.backup.clone()
+  #[test]
+  fn test_new_password() {
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let mut user = User::new_from_signup(&dir.as_ref().to_path_buf(), data.clone()).unwrap();
+    user.new_password();
+    assert_eq!(1, user.passwords().len());
   }
 
-  pub fn passwords(&self) -> Vec<PasswordType> {
-    self.passwords.clone()
+  #[test]
+  fn test_redacted_debug() {
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let mut user = User::new_from_signup(&dir.as_ref().to_path_buf(), data).unwrap();
+    let mut password = user.new_password();
+    password.password = Some(String::from("hunter2"));
+
+    let debug = format!("{:?}", password);
+    assert!(!debug.contains("hunter2"));
+    assert!(debug.contains(password.uuid.as_str()));
+
+    // the previous passwords are wiped with the entry
+    let mut copy = password.clone();
+    copy.history = vec![HistoryEntry::new(String::from("hunter1"))];
+    copy.zeroize();
+    assert!(copy.history.is_empty());
+    let mut entry = HistoryEntry::new(String::from("hunter1"));
+    entry.zeroize();
+    assert!(entry.password().is_empty());
+
+    // the wipe clears the decrypted data
+    user.update_password(password).unwrap();
+    user.new_folder(String::from("Work"), None).unwrap();
+    user.wipe();
+    assert!(user.passwords().is_empty());
+    assert!(user.folders().is_empty());
+    assert!(user.encryption.is_none());
   }
-}
 
-#[cfg(test)]
-mod tests {
-  use super::*;
-  use tempfile::TempDir;
+  #[test]
+  fn test_folders() {
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let mut user = User::new_from_signup(&path, data.clone()).unwrap();
+
+    let root = user.new_folder(String::from("Work"), None).unwrap();
+    let child = user
+      .new_folder(String::from("Servers"), Some(root.uuid().to_string()))
+      .unwrap();
+    assert!(matches!(
+      user.new_folder(String::from("Missing"), Some(String::from("missing"))),
+      Err(ConfigError::NotFoundError)
+    ));
+    assert!(matches!(
+      user.move_folder(root.uuid(), Some(child.uuid().to_string())),
+      Err(ConfigError::FolderCycleError)
+    ));
+    user
+      .rename_folder(child.uuid(), String::from("Hosts"))
+      .unwrap();
+
+    let mut password = user.new_password();
+    password.tags = vec![String::from("ssh")];
+    password.favorite = true;
+    user.update_password(password.clone()).unwrap();
+    user
+      .move_password(password.uuid.as_str(), Some(child.uuid().to_string()))
+      .unwrap();
+    // an update can not reference an unknown folder
+    let mut unknown = password.clone();
+    unknown.folder = Some(String::from("missing"));
+    assert!(matches!(
+      user.update_password(unknown),
+      Err(ConfigError::NotFoundError)
+    ));
+    user.write(&path).unwrap();
+    drop(user);
+
+    // stored encrypted and restored on the login
+    let content = fs::read_to_string(path.join("username.json")).unwrap();
+    assert!(!content.contains("Hosts"));
+    let mut user = User::new_from_login(&path, data).unwrap();
+    assert_eq!(2, user.folders().len());
+    match user.passwords().first().unwrap() {
+      PasswordType::Data(stored) => {
+        assert_eq!(Some(child.uuid().to_string()), stored.folder);
+        assert_eq!(vec![String::from("ssh")], stored.tags);
+        assert!(stored.favorite);
+      }
+      _ => panic!("Wrong enum"),
+    }
+
+    // the content moves up to the parent
+    user.delete_folder(child.uuid()).unwrap();
+    assert_eq!(1, user.folders().len());
+    match user.passwords().first().unwrap() {
+      PasswordType::Data(stored) => assert_eq!(Some(root.uuid().to_string()), stored.folder),
+      _ => panic!("Wrong enum"),
+    }
+  }
 
   #[test]
-  fn test_signup() {
+  fn test_items() {
     let data = UserData {
       username: String::from("username"),
       password: String::from("password"),
     };
     let dir = TempDir::new().unwrap();
-    User::new_from_signup(&dir.as_ref().to_path_buf(), data).unwrap();
-    dir.close().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let mut user = User::new_from_signup(&path, data.clone()).unwrap();
+
+    // cards have no password to analyse, even with the login fields set
+    let mut card = user.new_item(ItemKind::Card);
+    card.password = Some(String::from("1234"));
+    user.update_password(card).unwrap();
+    let mut login = user.new_password();
+    login.password = Some(String::from("1234"));
+    user.update_password(login.clone()).unwrap();
+
+    let result = user.analyse_passwords();
+    assert!(result.reused.is_empty());
+    assert_eq!(vec![login.uuid.clone()], result.very_weak);
+
+    user.write(&path).unwrap();
+    drop(user);
+    let user = User::new_from_login(&path, data).unwrap();
+    match user.passwords().first().unwrap() {
+      PasswordType::Data(stored) => assert_eq!(ItemKind::Card, stored.item.kind()),
+      _ => panic!("Wrong enum"),
+    }
   }
 
   #[test]
-  fn test_login() {
-    let mut data = UserData {
-      username: String::from("Username"),
+  fn test_custom_fields() {
+    let data = UserData {
+      username: String::from("username"),
       password: String::from("password"),
     };
     let dir = TempDir::new().unwrap();
-    let user = User::new_from_signup(&dir.as_ref().to_path_buf(), data.clone()).unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let mut user = User::new_from_signup(&path, data.clone()).unwrap();
 
-    // test case
-    data.username = String::from("UseRname");
+    let mut password = user.new_password();
+    password.fields = vec![
+      CustomField::new(String::from("pin"), String::from("1234"), FieldKind::Hidden),
+      CustomField::new(String::from("account"), String::from("42"), FieldKind::Text),
+    ];
+    user.update_password(password.clone()).unwrap();
+    assert_eq!(
+      vec!["Unnamed", "pin", "account", "42"],
+      password.search_terms()
+    );
 
-    let login = User::new_from_login(&dir.as_ref().to_path_buf(), data).unwrap();
-    dir.close().unwrap();
-    assert_eq!(user.username, login.username);
+    // invalid values are refused
+    let mut invalid = password.clone();
+    invalid.fields.push(CustomField::new(
+      String::from("active"),
+      String::from("yes"),
+      FieldKind::Boolean,
+    ));
+    assert!(matches!(
+      user.update_password(invalid),
+      Err(ConfigError::FieldError(_))
+    ));
+
+    user.write(&path).unwrap();
+    drop(user);
+    let user = User::new_from_login(&path, data).unwrap();
+    match user.passwords().first().unwrap() {
+      PasswordType::Data(stored) => {
+        assert_eq!(2, stored.fields.len());
+        assert_eq!("pin", stored.fields[0].label());
+      }
+      _ => panic!("Wrong enum"),
+    }
   }
 
   #[test]
-  #[should_panic]
-  fn test_auth() {
+  fn test_otp() {
     let data = UserData {
       username: String::from("username"),
       password: String::from("password"),
     };
     let dir = TempDir::new().unwrap();
-    User::new_from_signup(&dir.as_ref().to_path_buf(), data.clone()).unwrap();
+    let mut user = User::new_from_signup(&dir.as_ref().to_path_buf(), data).unwrap();
 
-    User::new_from_login(
-      &dir.as_ref().to_path_buf(),
-      UserData {
-        username: String::from("username"),
-        password: String::from("te"),
-      },
-    )
-    .unwrap();
+    let mut password = user.new_password();
+    assert!(matches!(
+      user.otp_code(password.uuid.as_str()),
+      Err(ConfigError::NotFoundError)
+    ));
+    password.otp = Some(String::from("invalid!"));
+    assert!(matches!(
+      user.update_password(password.clone()),
+      Err(ConfigError::OtpError(_))
+    ));
+
+    // rfc 4226 secret
+    password.otp = Some(String::from(
+      "otpauth://hotp/Example?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=0",
+    ));
+    user.update_password(password.clone()).unwrap();
+    assert_eq!(
+      "755224",
+      user.otp_code(password.uuid.as_str()).unwrap().code()
+    );
+    assert_eq!(
+      "287082",
+      user.next_hotp(password.uuid.as_str()).unwrap().code()
+    );
+    assert_eq!(
+      "287082",
+      user.otp_code(password.uuid.as_str()).unwrap().code()
+    );
   }
 
   #[test]
-  fn test_new_password() {
+  fn test_history() {
     let data = UserData {
       username: String::from("username"),
       password: String::from("password"),
     };
     let dir = TempDir::new().unwrap();
-    let mut user = User::new_from_signup(&dir.as_ref().to_path_buf(), data.clone()).unwrap();
-    user.new_password();
-    assert_eq!(1, user.passwords().len());
+    let path = dir.as_ref().to_path_buf();
+    let mut user = User::new_from_signup(&path, data.clone()).unwrap();
+
+    let mut password = user.new_password();
+    for value in ["first", "second", "third"].iter() {
+      password.password = Some(value.to_string());
+      user.update_password(password.clone()).unwrap();
+    }
+    // other changes are not tracked
+    password.name = Some(String::from("renamed"));
+    user.update_password(password.clone()).unwrap();
+
+    let uuid = password.uuid.as_str();
+    let history = user.password_history(uuid).unwrap();
+    assert_eq!(2, history.len());
+    assert_eq!("second", history[0].password());
+    assert_eq!("first", history[1].password());
+
+    let restored = user.restore_password_history(uuid, 1).unwrap();
+    assert_eq!(Some(String::from("first")), restored.password);
+    let history = user.password_history(uuid).unwrap();
+    assert_eq!("third", history[0].password());
+    assert_eq!("second", history[1].password());
+    assert!(matches!(
+      user.restore_password_history(uuid, 2),
+      Err(ConfigError::NotFoundError)
+    ));
+
+    user.update_history_retention(1);
+    user.write(&path).unwrap();
+    drop(user);
+
+    let user = User::new_from_login(&path, data).unwrap();
+    assert_eq!(1, user.history_retention());
+    assert_eq!(1, user.password_history(uuid).unwrap().len());
   }
 
   #[test]
@@ -522,7 +3213,7 @@ mod tests {
     let mut user = User::new_from_signup(&dir.as_ref().to_path_buf(), data.clone()).unwrap();
     let mut password = user.new_password();
     password.password = Some(String::from("test"));
-    user.update_password(password);
+    user.update_password(password).unwrap();
 
     assert_eq!(1, user.passwords().len());
     if let PasswordType::Data(pwd) = user.passwords().first().unwrap() {
@@ -544,4 +3235,547 @@ mod tests {
     user.delete_password(password);
     assert_eq!(0, user.passwords().len());
   }
+
+  #[test]
+  fn test_trash() {
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let mut user = User::new_from_signup(&path, data.clone()).unwrap();
+    let first = user.new_password();
+    let second = user.new_password();
+    let third = user.new_password();
+
+    user.delete_password(first.clone());
+    user.delete_password(second.clone());
+    assert_eq!(1, user.passwords().len());
+    assert_eq!(2, user.trash().len());
+
+    let restored = user.restore_password(first.uuid.as_str()).unwrap();
+    assert_eq!(None, restored.deleted);
+    assert!(matches!(
+      user.restore_password(third.uuid.as_str()),
+      Err(ConfigError::NotFoundError)
+    ));
+    assert_eq!(2, user.passwords().len());
+
+    user.purge_trash(Some(second.uuid.as_str()));
+    assert!(user.trash().is_empty());
+
+    // old entries are purged on the login
+    user.delete_password(third.clone());
+    // entries in the trash can not be changed
+    assert!(user.password_mut(third.uuid.as_str()).is_err());
+    user.trashed_mut(third.uuid.as_str()).unwrap().deleted = Some(0);
+    user.write(&path).unwrap();
+    drop(user);
+
+    let mut user = User::new_from_login(&path, data).unwrap();
+    assert!(user.trash().is_empty());
+    assert_eq!(1, user.passwords().len());
+
+    // a huge retention keeps the entries instead of overflowing
+    user.delete_password(third);
+    user.update_trash_retention(u64::MAX);
+    assert_eq!(0, user.purge_expired_trash(util::now()));
+  }
+
+  #[test]
+  fn test_timestamps() {
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let mut user = User::new_from_signup(&dir.as_ref().to_path_buf(), data).unwrap();
+
+    let mut password = user.new_password();
+    let uuid = password.uuid.clone();
+    assert!(password.created.is_some());
+    assert_eq!(password.created, password.password_changed);
+
+    // the timestamps sent by the frontend are ignored
+    password.password = Some(String::from("correct horse battery staple"));
+    password.created = Some(0);
+    user.update_password(password.clone()).unwrap();
+    user.mark_used(uuid.as_str()).unwrap();
+    let stored = user.password(uuid.as_str()).unwrap();
+    assert!(stored.created.unwrap() > 0);
+    assert!(stored.last_used.is_some());
+    assert!(user.analyse_passwords().old.is_empty());
+
+    // only changes of the password itself count
+    let changed = user.password(uuid.as_str()).unwrap().password_changed;
+    password.name = Some(String::from("renamed"));
+    user.update_password(password).unwrap();
+    assert_eq!(
+      changed,
+      user.password(uuid.as_str()).unwrap().password_changed
+    );
+
+    user.password_mut(uuid.as_str()).unwrap().password_changed = Some(0);
+    assert_eq!(vec![uuid.clone()], user.analyse_passwords().old);
+    user.update_password_max_age(0);
+    assert!(user.analyse_passwords().old.is_empty());
+    // a huge max age does not overflow
+    user.update_password_max_age(u64::MAX);
+    assert!(user.analyse_passwords().old.is_empty());
+  }
+
+  #[test]
+  fn test_attachments() {
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let blobs = path.join("attachments").join("username");
+    let mut user = User::new_from_signup(&path, data.clone()).unwrap();
+    let password = user.new_password();
+    let uuid = password.uuid.as_str();
+
+    let attachment = user
+      .add_attachment(&path, uuid, String::from("cert.pem"), b"certificate")
+      .unwrap();
+    user.write(&path).unwrap();
+    // only the ciphertext is saved
+    let blob = fs::read(blobs.join(attachment.id())).unwrap();
+    assert!(!blob.windows(11).any(|window| window == b"certificate"));
+    assert_eq!(
+      b"certificate".to_vec(),
+      user
+        .read_attachment(&path, uuid, attachment.id())
+        .unwrap()
+        .to_vec()
+    );
+
+    // the frontend does not manage the attachments
+    user.update_password(password.clone()).unwrap();
+    assert_eq!(1, user.attachments(uuid).unwrap().len());
+    assert!(matches!(
+      user.add_attachment(
+        &path,
+        uuid,
+        String::from("big"),
+        &vec![0; MAX_ATTACHMENT_SIZE + 1]
+      ),
+      Err(ConfigError::AttachmentSizeError)
+    ));
+
+    // the blob is removed after the write
+    let second = user
+      .add_attachment(&path, uuid, String::from("key.txt"), b"key")
+      .unwrap();
+    user.delete_attachment(uuid, second.id()).unwrap();
+    assert!(blobs.join(second.id()).exists());
+    user.write(&path).unwrap();
+    assert!(!blobs.join(second.id()).exists());
+    assert!(user.delete_attachment(uuid, second.id()).is_err());
+
+    // the key of the blob is only saved inside of the encrypted entry
+    let key = user.attachments(uuid).unwrap()[0].key().to_string();
+    let json = serde_json::to_string(&user.passwords()).unwrap();
+    assert!(json.contains(attachment.id()));
+    assert!(!json.contains(key.as_str()));
+    assert!(!serde_json::to_string(&attachment)
+      .unwrap()
+      .contains(key.as_str()));
+
+    // the snapshots keep the blobs of purged passwords
+    user.delete_password(password.clone());
+    user.purge_trash(None);
+    user.write(&path).unwrap();
+    assert!(blobs.join(attachment.id()).exists());
+    let snapshot = user.list_snapshots(&path).unwrap()[0].id();
+    user.restore_snapshot(&path, snapshot.as_str()).unwrap();
+    assert_eq!(
+      b"certificate".to_vec(),
+      user
+        .read_attachment(&path, uuid, attachment.id())
+        .unwrap()
+        .to_vec()
+    );
+
+    // the blob is removed once no snapshot references it
+    user.delete_password(password.clone());
+    user.purge_trash(None);
+    user.write(&path).unwrap();
+    Snapshots::new(&path, "username").clear().unwrap();
+    user.write(&path).unwrap();
+    assert!(!blobs.join(attachment.id()).exists());
+
+    // unreferenced blobs of an interrupted write are removed after the next login
+    let unsaved = user.new_password();
+    let third = user
+      .add_attachment(&path, unsaved.uuid.as_str(), String::from("a"), b"a")
+      .unwrap();
+    drop(user);
+    let user = User::new_from_login(&path, data).unwrap();
+    assert!(blobs.join(third.id()).exists());
+    Snapshots::new(&path, "username").clear().unwrap();
+    user.write(&path).unwrap();
+    assert!(!blobs.join(third.id()).exists());
+  }
+
+  #[test]
+  fn test_import() {
+    use crate::model::import::{self, ImportFormat};
+
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let mut user = User::new_from_signup(&dir.as_ref().to_path_buf(), data).unwrap();
+    let mut existing = user.new_password();
+    existing.url = Some(String::from("https://github.com/"));
+    existing.login = Some(String::from("alice"));
+    existing.password = Some(String::from("hunter2"));
+    user.update_password(existing).unwrap();
+
+    let csv = include_bytes!("../../tests/fixtures/import/chrome.csv");
+    let report = user
+      .import_passwords(
+        &dir.as_ref().to_path_buf(),
+        import::parse(ImportFormat::BrowserCsv, csv, None, None).unwrap(),
+      )
+      .unwrap();
+    // github.com already exists
+    assert_eq!(2, report.imported);
+    assert_eq!(1, report.duplicates);
+    assert_eq!(2, report.skipped.len());
+    assert_eq!(3, user.passwords().len());
+
+    // importing twice does not add anything
+    let report = user
+      .import_passwords(
+        &dir.as_ref().to_path_buf(),
+        import::parse(ImportFormat::BrowserCsv, csv, None, None).unwrap(),
+      )
+      .unwrap();
+    assert_eq!(0, report.imported);
+    assert_eq!(3, report.duplicates);
+
+    // the login of bitwarden is a duplicate as well
+    let json = include_bytes!("../../tests/fixtures/import/bitwarden.json");
+    let report = user
+      .import_passwords(
+        &dir.as_ref().to_path_buf(),
+        import::parse(ImportFormat::Bitwarden, json, None, None).unwrap(),
+      )
+      .unwrap();
+    assert_eq!(3, report.imported);
+    assert_eq!(1, report.duplicates);
+    let card = user
+      .passwords()
+      .into_iter()
+      .find_map(|ty| match ty {
+        PasswordType::Data(password) if password.name == Some(String::from("Visa")) => {
+          Some(password)
+        }
+        _ => None,
+      })
+      .unwrap();
+    assert_eq!(ItemKind::Card, card.item.kind());
+    assert_eq!(Some(user.folders()[0].uuid().to_string()), card.folder);
+
+    // nested folders are created once
+    let xml = include_bytes!("../../tests/fixtures/import/keepass.xml");
+    for _ in 0..2 {
+      user
+        .import_passwords(
+          &dir.as_ref().to_path_buf(),
+          import::parse(ImportFormat::KeepassXml, xml, None, None).unwrap(),
+        )
+        .unwrap();
+    }
+    let folders = user.folders();
+    assert_eq!(3, folders.len());
+    let internet = folders
+      .iter()
+      .find(|folder| folder.name() == "Internet")
+      .unwrap();
+    let home = folders
+      .iter()
+      .find(|folder| folder.name() == "Home")
+      .unwrap();
+    assert_eq!(None, internet.parent());
+    assert_eq!(Some(&internet.uuid().to_string()), home.parent());
+
+    // the attachments of a database are encrypted into the vault
+    let kdbx = include_bytes!("../../tests/fixtures/import/keepass_aes.kdbx");
+    let import = import::parse(ImportFormat::Kdbx, kdbx, Some("kdbx password"), None).unwrap();
+    let report = user
+      .import_passwords(&dir.as_ref().to_path_buf(), import)
+      .unwrap();
+    assert_eq!(3, report.imported);
+    let email = user
+      .passwords()
+      .into_iter()
+      .find_map(|ty| match ty {
+        PasswordType::Data(password) if password.name == Some(String::from("Email")) => {
+          Some(password)
+        }
+        _ => None,
+      })
+      .unwrap();
+    assert_eq!(1, email.attachments.len());
+    let data = user
+      .read_attachment(
+        &dir.as_ref().to_path_buf(),
+        email.uuid.as_str(),
+        email.attachments[0].id(),
+      )
+      .unwrap();
+    assert_eq!(b"write this down\n".to_vec(), *data);
+  }
+
+  #[test]
+  fn test_import_failure() {
+    use crate::model::import::{Import, ImportedAttachment, ImportedEntry};
+
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let mut user = User::new_from_signup(&path, data).unwrap();
+    // the blobs can not be written
+    fs::write(path.join("attachments"), "blocked").unwrap();
+
+    let mut first = ImportedEntry::default();
+    first.name = Some(String::from("First"));
+    first.folder = vec![String::from("Imported")];
+    let mut second = ImportedEntry::default();
+    second.name = Some(String::from("Second"));
+    second.attachments.push(ImportedAttachment {
+      name: String::from("file.txt"),
+      data: b"content".to_vec(),
+    });
+    let import = Import {
+      entries: vec![first, second],
+      skipped: Vec::new(),
+    };
+    assert!(user.import_passwords(&path, import).is_err());
+
+    // neither the entry before the failed one nor its folder were added
+    assert!(user.passwords().is_empty());
+    assert!(user.folders().is_empty());
+  }
+
+  #[test]
+  fn test_import_fields() {
+    use crate::model::import::{Import, ImportedEntry};
+
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let mut user = User::new_from_signup(&dir.as_ref().to_path_buf(), data).unwrap();
+    let mut entry = ImportedEntry::default();
+    entry.name = Some(String::from("Fields"));
+    entry.fields = vec![
+      CustomField::new(
+        String::from("birthday"),
+        String::from("17.05.1990"),
+        FieldKind::Date,
+      ),
+      CustomField::new(
+        String::from("active"),
+        String::from("yes"),
+        FieldKind::Boolean,
+      ),
+      CustomField::new(String::from("pin"), String::from("1234"), FieldKind::Hidden),
+    ];
+
+    let import = Import {
+      entries: vec![entry],
+      skipped: Vec::new(),
+    };
+    user
+      .import_passwords(&dir.as_ref().to_path_buf(), import)
+      .unwrap();
+
+    // the invalid values are kept as text, so the entry can still be saved
+    let mut password = match user.passwords().pop().unwrap() {
+      PasswordType::Data(password) => password,
+      _ => panic!("expected an entry"),
+    };
+    assert!(password.fields.iter().all(|field| field.validate().is_ok()));
+    assert_eq!(Some("17.05.1990"), password.fields[0].searchable_value());
+    assert_eq!(None, password.fields[2].searchable_value());
+    password.name = Some(String::from("Renamed"));
+    user.update_password(password).unwrap();
+  }
+
+  #[test]
+  fn test_search() {
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let mut user = User::new_from_signup(&dir.as_ref().to_path_buf(), data).unwrap();
+    let folder = user.new_folder(String::from("Work"), None).unwrap();
+
+    let mut github = user.new_password();
+    github.name = Some(String::from("GitHub"));
+    github.login = Some(String::from("alice"));
+    github.password = Some(String::from("hunter2"));
+    github.folder = Some(folder.uuid().to_string());
+    user.update_password(github.clone()).unwrap();
+    let mut gitlab = user.new_password();
+    gitlab.name = Some(String::from("GitLab"));
+    gitlab.password = Some(String::from("hunter2"));
+    gitlab.otp = Some(String::from("JBSWY3DPEHPK3PXP"));
+    user.update_password(gitlab.clone()).unwrap();
+    let mut note = user.new_item(ItemKind::Note);
+    note.name = Some(String::from("Recovery"));
+    note.description = Some(String::from("github recovery codes"));
+    user.update_password(note.clone()).unwrap();
+
+    let search = |query: serde_json::Value| {
+      user
+        .search_passwords(&serde_json::from_value::<SearchQuery>(query).unwrap())
+        .into_iter()
+        .map(|summary| summary.uuid)
+        .collect::<Vec<String>>()
+    };
+    // the exact name first, then the notes
+    assert_eq!(
+      vec![github.uuid.clone(), note.uuid.clone()],
+      search(serde_json::json!({ "query": "github" }))
+    );
+    assert_eq!(3, search(serde_json::json!({ "query": "" })).len());
+    assert_eq!(
+      vec![github.uuid.clone()],
+      search(serde_json::json!({ "folder": folder.uuid() }))
+    );
+    assert_eq!(
+      vec![note.uuid.clone()],
+      search(serde_json::json!({ "kind": "note" }))
+    );
+    assert_eq!(
+      vec![gitlab.uuid.clone()],
+      search(serde_json::json!({ "otp": true }))
+    );
+    assert_eq!(
+      2,
+      search(serde_json::json!({ "reused": true, "weak": true })).len()
+    );
+    assert_eq!(
+      vec![github.uuid.clone(), gitlab.uuid.clone(), note.uuid.clone()],
+      search(serde_json::json!({ "sort": "name" }))
+    );
+
+    // the secrets are only returned on demand
+    let summary = serde_json::to_string(&user.search_passwords(&SearchQuery::default())).unwrap();
+    assert!(!summary.contains("hunter2"));
+    assert_eq!(
+      Some(String::from("hunter2")),
+      user.reveal_password(github.uuid.as_str()).unwrap()
+    );
+  }
+
+  #[test]
+  fn test_backup_sync() {
+    let worker = MockWorker::start();
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let mut user = User::new_from_signup(&path, data.clone()).unwrap();
+    user.enable_backup(&path, worker.url.clone(), None).unwrap();
+    let uuid = user.backup().unwrap().uuid();
+
+    // push one password
+    let mut password = user.new_password();
+    password.password = Some(String::from("test"));
+    user.update_password(password).unwrap();
+    user.push_backup().unwrap();
+    assert!(worker.data(uuid.as_str()).unwrap().contains("passwords"));
+
+    // the credentials are encrypted on the disk
+    let content = fs::read_to_string(path.join("username.json")).unwrap();
+    assert!(!content.contains(uuid.as_str()));
+    drop(user);
+
+    // pull into a fresh session
+    let mut login = User::new_from_login(&path, data).unwrap();
+    assert_eq!(0, login.passwords().len());
+    assert_eq!(uuid, login.backup().unwrap().uuid());
+    login.update_trash_retention(1);
+    login.pull_backup(&path).unwrap();
+    assert_eq!(1, login.passwords().len());
+    // the settings are pulled with the data
+    assert_eq!(DEFAULT_TRASH_RETENTION, login.trash_retention());
+    // the local vault is kept as snapshot
+    let snapshots = Snapshots::new(&path, "username");
+    let content = snapshots
+      .read(snapshots.list().unwrap()[0].id().as_str())
+      .unwrap();
+    assert_eq!(
+      1,
+      RawUser::parse(content.as_str())
+        .unwrap()
+        .unseal(login.encryption.as_ref().unwrap())
+        .unwrap()
+        .trash_retention
+    );
+  }
+
+  #[test]
+  fn test_backup_empty() {
+    let worker = MockWorker::start();
+    let data = UserData {
+      username: String::from("username"),
+      password: String::from("password"),
+    };
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let mut user = User::new_from_signup(&path, data).unwrap();
+    user.enable_backup(&path, worker.url.clone(), None).unwrap();
+    let password = user.new_password();
+    user.update_password(password).unwrap();
+
+    // nothing to pull, the local vault stays untouched
+    assert!(matches!(
+      user.pull_backup(&path),
+      Err(ConfigError::EmptyBackupError)
+    ));
+    assert_eq!(1, user.passwords().len());
+  }
+
+  #[test]
+  fn test_backup_link() {
+    let worker = MockWorker::start();
+    let dir = TempDir::new().unwrap();
+    let path = dir.as_ref().to_path_buf();
+    let mut user = User::new_from_signup(
+      &path,
+      UserData {
+        username: String::from("username"),
+        password: String::from("password"),
+      },
+    )
+    .unwrap();
+
+    // unknown credentials
+    assert!(user
+      .enable_backup(
+        &path,
+        worker.url.clone(),
+        Some((String::from("uuid"), String::from("password")))
+      )
+      .is_err());
+    assert!(user.backup().is_none());
+  }
 }
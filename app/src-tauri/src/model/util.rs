@@ -0,0 +1,113 @@
+/*
+ * MIT LICENSE
+ *
+ * Copyright (c) 2022 Randoooom
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// one day in milliseconds
+pub const DAY: u64 = 24 * 60 * 60 * 1000;
+
+/// unix timestamp in milliseconds
+pub fn now() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap()
+    .as_millis() as u64
+}
+
+/// number of days of the month, february depends on the leap years
+pub fn days_in_month(year: i64, month: u32) -> u32 {
+  match month {
+    2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+    2 => 28,
+    4 | 6 | 9 | 11 => 30,
+    _ => 31,
+  }
+}
+
+/// days since the unix epoch of a date of the proleptic gregorian calendar
+///
+/// none for invalid dates and years out of range
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> Option<i64> {
+  if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+    return None;
+  }
+
+  // the years start in march, so the leap day is the last one
+  let year = if month <= 2 {
+    year.checked_sub(1)?
+  } else {
+    year
+  };
+  let era = year.div_euclid(400);
+  let year_of_era = year.rem_euclid(400);
+  let day_of_year = (153 * ((i64::from(month) + 9) % 12) + 2) / 5 + i64::from(day) - 1;
+  let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+  era.checked_mul(146_097)?.checked_add(day_of_era - 719_468)
+}
+
+/// date of the proleptic gregorian calendar of the days since the unix epoch
+pub fn civil_from_days(days: i64) -> Option<(i64, u32, u32)> {
+  let days = days.checked_add(719_468)?;
+  let era = days.div_euclid(146_097);
+  let day_of_era = days.rem_euclid(146_097);
+  let year_of_era =
+    (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+  let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let month = (5 * day_of_year + 2) / 153;
+  let day = day_of_year - (153 * month + 2) / 5 + 1;
+  let month = if month < 10 { month + 3 } else { month - 9 };
+  let year = era * 400 + year_of_era + if month <= 2 { 1 } else { 0 };
+  Some((year, month as u32, day as u32))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_days_from_civil() {
+    assert_eq!(Some(0), days_from_civil(1970, 1, 1));
+    assert_eq!(Some(11_016), days_from_civil(2000, 2, 29));
+    assert_eq!(Some(-719_162), days_from_civil(1, 1, 1));
+    assert_eq!(None, days_from_civil(2022, 2, 29));
+    assert_eq!(None, days_from_civil(2022, 4, 31));
+    assert_eq!(None, days_from_civil(2022, 13, 1));
+    assert_eq!(None, days_from_civil(i64::MAX, 1, 1));
+    assert_eq!(None, days_from_civil(i64::MIN, 1, 1));
+  }
+
+  #[test]
+  fn test_civil_from_days() {
+    assert_eq!(Some((1970, 1, 1)), civil_from_days(0));
+    assert_eq!(Some((2000, 2, 29)), civil_from_days(11_016));
+    assert_eq!(Some((1969, 12, 31)), civil_from_days(-1));
+    assert_eq!(None, civil_from_days(i64::MAX));
+    // both directions match
+    for days in -800_000..800_000 {
+      let (year, month, day) = civil_from_days(days).unwrap();
+      assert_eq!(Some(days), days_from_civil(year, month, day));
+    }
+  }
+}